@@ -0,0 +1,295 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+use std::sync::{OnceLock, RwLock};
+use volo_grpc::Status;
+
+/// 访问权限
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum AccessRight {
+    /// `*`，包含全部操作
+    All,
+    Read,
+    Write,
+    Delete,
+    /// 通过 [`OperationRegistry`] 注册的自定义操作
+    Custom(String),
+}
+
+impl AccessRight {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AccessRight::All => "*",
+            AccessRight::Read => "read",
+            AccessRight::Write => "write",
+            AccessRight::Delete => "delete",
+            AccessRight::Custom(operation) => operation,
+        }
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        !matches!(self, AccessRight::Custom(_))
+    }
+}
+
+impl From<&str> for AccessRight {
+    fn from(operation: &str) -> Self {
+        match operation {
+            "*" => AccessRight::All,
+            "read" => AccessRight::Read,
+            "write" => AccessRight::Write,
+            "delete" => AccessRight::Delete,
+            _ => AccessRight::Custom(operation.to_owned()),
+        }
+    }
+}
+
+impl From<String> for AccessRight {
+    fn from(operation: String) -> Self {
+        AccessRight::from(operation.as_str())
+    }
+}
+
+impl From<AccessRight> for String {
+    fn from(right: AccessRight) -> Self {
+        right.as_str().to_owned()
+    }
+}
+
+impl Display for AccessRight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// 关联边上的访问权限集，以字符串数组存储在边的 `operations` 属性中
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AccessRightSet(BTreeSet<AccessRight>);
+
+impl AccessRightSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, right: AccessRight) -> bool {
+        self.0.insert(right)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AccessRight> {
+        self.0.iter()
+    }
+
+    /// `*` 授予全部操作
+    pub fn grants(&self, operation: &str) -> bool {
+        self.0.contains(&AccessRight::All) || self.0.contains(&AccessRight::from(operation))
+    }
+
+    pub fn intersection(&self, other: &AccessRightSet) -> AccessRightSet {
+        AccessRightSet(self.0.intersection(&other.0).cloned().collect())
+    }
+
+    pub fn union(&self, other: &AccessRightSet) -> AccessRightSet {
+        AccessRightSet(self.0.union(&other.0).cloned().collect())
+    }
+
+    /// 把 `*` 展开为这些资源类型支持的全部操作（含内置操作），其余操作保持不变
+    pub fn expand_all<S: AsRef<str>>(&self, resource_types: &[S]) -> AccessRightSet {
+        if !self.0.contains(&AccessRight::All) {
+            return self.clone();
+        }
+        let mut expanded: AccessRightSet = self
+            .iter()
+            .filter(|right| **right != AccessRight::All)
+            .cloned()
+            .chain([AccessRight::Read, AccessRight::Write, AccessRight::Delete])
+            .collect();
+        for resource_type in resource_types {
+            expanded = expanded.union(&OperationRegistry::operations(resource_type.as_ref()));
+        }
+        expanded
+    }
+
+    /// 所有操作都需为内置操作或已注册的自定义操作
+    pub fn validate(&self) -> Result<(), Status> {
+        match self
+            .0
+            .iter()
+            .find(|right| !OperationRegistry::is_known(right))
+        {
+            Some(right) => Err(Status::invalid_argument(format!(
+                "unknown operation: {}",
+                right
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// 所有操作都需为内置操作，或为其中某个资源类型注册的自定义操作
+    ///
+    /// 没有资源类型时只允许内置操作。
+    pub fn validate_for<S: AsRef<str>>(&self, resource_types: &[S]) -> Result<(), Status> {
+        match self.0.iter().find(|right| {
+            !right.is_builtin()
+                && !resource_types
+                    .iter()
+                    .any(|resource_type| OperationRegistry::supports(resource_type.as_ref(), right))
+        }) {
+            Some(right) => Err(Status::invalid_argument(format!(
+                "operation: {} is not supported by resource type: [{}]",
+                right,
+                resource_types
+                    .iter()
+                    .map(AsRef::as_ref)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::Array(
+            self.0
+                .iter()
+                .map(|right| Value::String(right.to_string()))
+                .collect(),
+        )
+    }
+
+    /// 解析边属性中的权限集，属性缺失或格式不符时为空集
+    pub fn from_value(value: Option<&Value>) -> Self {
+        value
+            .and_then(Value::as_array)
+            .map(|operations| {
+                operations
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(AccessRight::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl FromIterator<AccessRight> for AccessRightSet {
+    fn from_iter<I: IntoIterator<Item = AccessRight>>(iter: I) -> Self {
+        AccessRightSet(iter.into_iter().collect())
+    }
+}
+
+impl<S: AsRef<str>> From<&[S]> for AccessRightSet {
+    fn from(operations: &[S]) -> Self {
+        operations
+            .iter()
+            .map(|operation| AccessRight::from(operation.as_ref()))
+            .collect()
+    }
+}
+
+pub struct OperationRegistry;
+
+/// 资源类型 → 该类资源支持的自定义操作
+static OPERATION_REGISTRY: OnceLock<RwLock<HashMap<String, BTreeSet<AccessRight>>>> =
+    OnceLock::new();
+
+impl OperationRegistry {
+    fn registry() -> &'static RwLock<HashMap<String, BTreeSet<AccessRight>>> {
+        OPERATION_REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+    }
+
+    /// 为资源类型注册操作，已存在时合并
+    pub fn register<S: AsRef<str>>(resource_type: &str, operations: &[S]) {
+        let mut registry = OperationRegistry::registry()
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        registry
+            .entry(resource_type.to_owned())
+            .or_default()
+            .extend(operations.iter().map(|op| AccessRight::from(op.as_ref())));
+    }
+
+    pub fn unregister(resource_type: &str) -> bool {
+        OperationRegistry::registry()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(resource_type)
+            .is_some()
+    }
+
+    /// 资源类型支持的操作，包含内置的 read/write/delete
+    pub fn operations(resource_type: &str) -> AccessRightSet {
+        let mut operations: AccessRightSet =
+            [AccessRight::Read, AccessRight::Write, AccessRight::Delete]
+                .into_iter()
+                .collect();
+        if let Some(custom) = OperationRegistry::registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(resource_type)
+        {
+            for right in custom {
+                operations.insert(right.clone());
+            }
+        }
+        operations
+    }
+
+    /// 资源类型是否支持该操作，内置操作总是支持
+    pub fn supports(resource_type: &str, right: &AccessRight) -> bool {
+        right.is_builtin()
+            || OperationRegistry::registry()
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(resource_type)
+                .is_some_and(|operations| operations.contains(right))
+    }
+
+    /// 从 JSON 对象 `{"资源类型": ["操作", ...]}` 注册操作，返回资源类型的数量
+    pub fn load_json(json: &str) -> Result<usize, Status> {
+        let registry: HashMap<String, Vec<String>> =
+            serde_json::from_str(json).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        for (resource_type, operations) in registry.iter() {
+            OperationRegistry::register(resource_type, operations);
+        }
+        Ok(registry.len())
+    }
+
+    /// 内置操作或任一资源类型注册过的操作
+    pub fn is_known(right: &AccessRight) -> bool {
+        right.is_builtin()
+            || OperationRegistry::registry()
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .values()
+                .any(|operations| operations.contains(right))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessRightSet, OperationRegistry};
+
+    #[test]
+    fn operations_are_validated_per_resource_type() {
+        OperationRegistry::load_json(r#"{"test-document": ["share"], "test-report": ["publish"]}"#)
+            .unwrap();
+        let share = AccessRightSet::from(&["read", "share"][..]);
+
+        assert!(share.validate_for(&["test-document"]).is_ok());
+        assert!(share.validate_for(&["test-report"]).is_err());
+        assert!(share
+            .validate_for(&["test-report", "test-document"])
+            .is_ok());
+        assert!(share.validate_for::<&str>(&[]).is_err());
+        assert!(AccessRightSet::from(&["*", "read"][..])
+            .validate_for::<&str>(&[])
+            .is_ok());
+    }
+}
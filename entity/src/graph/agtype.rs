@@ -0,0 +1,76 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::error::Error;
+use tokio_postgres::{
+    types::{FromSql, Type},
+    Row,
+};
+use volo_grpc::Status;
+
+/// 任意 agtype 结果，解析为 JSON
+///
+/// AGE 在文本表示中为顶点、边、路径和 numeric 附加 `::vertex` 等类型后缀，解析前去掉。
+#[derive(Debug, Clone)]
+pub struct AgValue(pub Value);
+
+impl<'a> FromSql<'a> for AgValue {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        // 二进制格式首字节为 agtype 版本号，其后为文本表示
+        let text = std::str::from_utf8(raw.get(1..).unwrap_or_default())?;
+        Ok(AgValue(serde_json::from_str(&strip_type_annotations(
+            text,
+        ))?))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "agtype"
+    }
+}
+
+/// 去掉字符串字面量以外的 `::identifier` 类型后缀
+fn strip_type_annotations(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    let mut escaped = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            result.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            while chars
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+            {
+                chars.next();
+            }
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// 将单列 agtype 结果行反序列化为 T
+pub fn rows_to<T: DeserializeOwned>(rows: &[Row]) -> Result<Vec<T>, Status> {
+    rows.iter()
+        .map(|row| {
+            let AgValue(value) = row
+                .try_get(0)
+                .map_err(|e| Status::from_error(Box::new(e)))?;
+            serde_json::from_value(value).map_err(|e| Status::from_error(Box::new(e)))
+        })
+        .collect()
+}
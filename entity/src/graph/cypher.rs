@@ -0,0 +1,494 @@
+use apache_age::AgType;
+use pilota::{AHashMap, FastStr};
+use serde_json::{Map, Value};
+use std::fmt::Display;
+use volo_grpc::Status;
+
+use super::{NodeType, AND, CREATE, MATCH, RETURN, SET, WHERE};
+
+pub const DELETE: &str = "DELETE";
+pub const DETACH: &str = "DETACH";
+pub const WITH: &str = "WITH";
+
+/// 标识符（别名、标签、属性名）只能拼接进 cypher，不能参数化，
+/// 因此只接受普通标识符，其余一律拒绝，避免改变语句语义或破坏外层 `$$` 引号
+pub fn is_identifier(ident: &str) -> bool {
+    let mut chars = ident.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[derive(Debug, Clone)]
+pub struct NodePattern {
+    alias: String,
+    label: Option<String>,
+    properties: Vec<(String, Value)>,
+}
+
+impl NodePattern {
+    pub fn new(alias: &str) -> Self {
+        Self {
+            alias: alias.to_owned(),
+            label: None,
+            properties: Vec::new(),
+        }
+    }
+
+    /// 以节点类型的缩写为别名、全称为标签，如 `(u: User)`
+    pub fn of(node_type: &NodeType) -> Self {
+        Self::new(&node_type.to_string()).label(&node_type.fmt_full())
+    }
+
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.alias = alias.to_owned();
+        self
+    }
+
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_owned());
+        self
+    }
+
+    pub fn property(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.properties.push((key.to_owned(), value.into()));
+        self
+    }
+
+    pub fn properties(mut self, properties: &AHashMap<FastStr, FastStr>) -> Self {
+        for (k, v) in properties.iter() {
+            self.properties
+                .push((k.to_string(), Value::String(v.to_string())));
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EdgePattern {
+    alias: Option<String>,
+    label: Option<String>,
+    properties: Vec<(String, Value)>,
+    /// 变长路径的跳数范围，`Some((None, None))` 表示 `*`
+    hops: Option<(Option<u32>, Option<u32>)>,
+}
+
+impl EdgePattern {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.alias = Some(alias.to_owned());
+        self
+    }
+
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_owned());
+        self
+    }
+
+    pub fn property(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.properties.push((key.to_owned(), value.into()));
+        self
+    }
+
+    /// 任意跳数，即 `-[*]->`
+    pub fn any_length(mut self) -> Self {
+        self.hops = Some((None, None));
+        self
+    }
+
+    pub fn hops(mut self, min: Option<u32>, max: Option<u32>) -> Self {
+        self.hops = Some((min, max));
+        self
+    }
+}
+
+/// 由节点和有向边交替组成的路径，如 `p = (a)-[r]->(b)`
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    path_alias: Option<String>,
+    start: NodePattern,
+    steps: Vec<(EdgePattern, NodePattern)>,
+}
+
+impl Pattern {
+    pub fn node(node: NodePattern) -> Self {
+        Self {
+            path_alias: None,
+            start: node,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn to(mut self, edge: EdgePattern, node: NodePattern) -> Self {
+        self.steps.push((edge, node));
+        self
+    }
+
+    pub fn path(mut self, alias: &str) -> Self {
+        self.path_alias = Some(alias.to_owned());
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Condition {
+    /// `id(alias) = id`
+    Id(String, i64),
+    /// `id(alias) IN [ids]`
+    IdIn(String, Vec<i64>),
+    /// `alias.key = $param`
+    Property(String, String, Value),
+    /// `label(alias) IN ['A', 'B']`，标签同样需为普通标识符
+    LabelIn(String, Vec<String>),
+    /// 由调用方保证安全的原始条件
+    Raw(String),
+}
+
+impl Condition {
+    pub fn id(alias: impl Display, id: i64) -> Self {
+        Condition::Id(alias.to_string(), id)
+    }
+
+    pub fn id_in(alias: impl Display, ids: Vec<i64>) -> Self {
+        Condition::IdIn(alias.to_string(), ids)
+    }
+
+    pub fn property(alias: impl Display, key: &str, value: impl Into<Value>) -> Self {
+        Condition::Property(alias.to_string(), key.to_owned(), value.into())
+    }
+
+    pub fn label_in<S: AsRef<str>>(alias: impl Display, labels: &[S]) -> Self {
+        Condition::LabelIn(
+            alias.to_string(),
+            labels.iter().map(|l| l.as_ref().to_owned()).collect(),
+        )
+    }
+
+    pub fn properties(alias: impl Display, properties: &AHashMap<FastStr, FastStr>) -> Vec<Self> {
+        let alias = alias.to_string();
+        properties
+            .iter()
+            .map(|(k, v)| Condition::property(&alias, k, v.to_string()))
+            .collect()
+    }
+}
+
+/// 参数化的 cypher 构造器
+///
+/// 所有用户输入的值都绑定为 agtype 参数（`$p0`, `$p1`…），随语句一起以参数 map 传给 AGE，
+/// 拼接进语句的只有经过 [`is_identifier`] 校验的别名、标签和属性名。
+#[derive(Debug, Default)]
+pub struct CypherQuery {
+    clauses: Vec<String>,
+    params: Map<String, Value>,
+    error: Option<String>,
+}
+
+impl CypherQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn bind(&mut self, value: Value) -> String {
+        let name = format!("p{}", self.params.len());
+        self.params.insert(name.clone(), value);
+        format!("${}", name)
+    }
+
+    fn ident(&mut self, ident: &str) -> String {
+        if !is_identifier(ident) && self.error.is_none() {
+            self.error = Some(format!("invalid cypher identifier: {:?}", ident));
+        }
+        ident.to_owned()
+    }
+
+    fn render_properties(&mut self, properties: Vec<(String, Value)>) -> String {
+        if properties.is_empty() {
+            return String::new();
+        }
+        let mut pairs = Vec::with_capacity(properties.len());
+        for (k, v) in properties {
+            let key = self.ident(&k);
+            let param = self.bind(v);
+            pairs.push(format!("{}: {}", key, param));
+        }
+        format!(" {{{}}}", pairs.join(", "))
+    }
+
+    fn render_node(&mut self, node: NodePattern) -> String {
+        let alias = self.ident(&node.alias);
+        let label = match node.label {
+            Some(label) => format!(": {}", self.ident(&label)),
+            None => String::new(),
+        };
+        let properties = self.render_properties(node.properties);
+        format!("({}{}{})", alias, label, properties)
+    }
+
+    fn render_edge(&mut self, edge: EdgePattern) -> String {
+        let alias = match edge.alias {
+            Some(alias) => self.ident(&alias),
+            None => String::new(),
+        };
+        let label = match edge.label {
+            Some(label) => format!(":{}", self.ident(&label)),
+            None => String::new(),
+        };
+        let hops = match edge.hops {
+            None => String::new(),
+            Some((None, None)) => "*".to_owned(),
+            Some((min, max)) => format!(
+                "*{}..{}",
+                min.map(|m| m.to_string()).unwrap_or_default(),
+                max.map(|m| m.to_string()).unwrap_or_default()
+            ),
+        };
+        let properties = self.render_properties(edge.properties);
+        format!("-[{}{}{}{}]->", alias, label, hops, properties)
+    }
+
+    fn render_pattern(&mut self, pattern: Pattern) -> String {
+        let mut rendered = match pattern.path_alias {
+            Some(alias) => format!("{} = ", self.ident(&alias)),
+            None => String::new(),
+        };
+        rendered.push_str(&self.render_node(pattern.start));
+        for (edge, node) in pattern.steps {
+            rendered.push_str(&self.render_edge(edge));
+            rendered.push_str(&self.render_node(node));
+        }
+        rendered
+    }
+
+    fn render_condition(&mut self, condition: Condition) -> String {
+        match condition {
+            Condition::Id(alias, id) => format!("id({}) = {}", self.ident(&alias), id),
+            Condition::IdIn(alias, ids) => format!(
+                "id({}) IN [{}]",
+                self.ident(&alias),
+                ids.iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Condition::Property(alias, key, value) => {
+                let alias = self.ident(&alias);
+                let key = self.ident(&key);
+                let param = self.bind(value);
+                format!("{}.{} = {}", alias, key, param)
+            }
+            Condition::LabelIn(alias, labels) => {
+                let alias = self.ident(&alias);
+                let labels = labels
+                    .iter()
+                    .map(|label| format!("'{}'", self.ident(label)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("label({}) IN [{}]", alias, labels)
+            }
+            Condition::Raw(raw) => raw,
+        }
+    }
+
+    fn patterns_clause(&mut self, keyword: &str, patterns: impl IntoIterator<Item = Pattern>) {
+        let rendered = patterns
+            .into_iter()
+            .map(|p| self.render_pattern(p))
+            .collect::<Vec<_>>();
+        self.clauses
+            .push(format!("{} {}", keyword, rendered.join(", ")));
+    }
+
+    pub fn match_(mut self, patterns: impl IntoIterator<Item = Pattern>) -> Self {
+        self.patterns_clause(MATCH, patterns);
+        self
+    }
+
+    pub fn create(mut self, patterns: impl IntoIterator<Item = Pattern>) -> Self {
+        self.patterns_clause(CREATE, patterns);
+        self
+    }
+
+    /// 多个条件以 AND 连接，条件为空时不生成 WHERE
+    pub fn where_(mut self, conditions: impl IntoIterator<Item = Condition>) -> Self {
+        let rendered = conditions
+            .into_iter()
+            .map(|c| self.render_condition(c))
+            .collect::<Vec<_>>();
+        if !rendered.is_empty() {
+            self.clauses.push(format!(
+                "{} {}",
+                WHERE,
+                rendered.join(&format!(" {} ", AND))
+            ));
+        }
+        self
+    }
+
+    /// `SET alias.key = $param, ...`，属性为空时不生成 SET
+    pub fn set(mut self, alias: &str, properties: Vec<(String, Value)>) -> Self {
+        if properties.is_empty() {
+            return self;
+        }
+        let alias = self.ident(alias);
+        let mut assignments = Vec::with_capacity(properties.len());
+        for (k, v) in properties {
+            let key = self.ident(&k);
+            let param = self.bind(v);
+            assignments.push(format!("{}.{} = {}", alias, key, param));
+        }
+        self.clauses
+            .push(format!("{} {}", SET, assignments.join(", ")));
+        self
+    }
+
+    pub fn delete(mut self, aliases: &[&str], detach: bool) -> Self {
+        let aliases = aliases
+            .iter()
+            .map(|a| self.ident(a))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if detach {
+            self.clauses
+                .push(format!("{} {} {}", DETACH, DELETE, aliases));
+        } else {
+            self.clauses.push(format!("{} {}", DELETE, aliases));
+        }
+        self
+    }
+
+    /// `WITH` 子句，表达式由调用方保证安全
+    pub fn with(mut self, expression: &str) -> Self {
+        self.clauses.push(format!("{} {}", WITH, expression));
+        self
+    }
+
+    /// `RETURN` 子句，表达式由调用方保证安全；AGE 的结果只有一列，表达式需为单个值
+    pub fn return_(mut self, expression: &str) -> Self {
+        self.clauses.push(format!("{} {}", RETURN, expression));
+        self
+    }
+
+    /// 生成 cypher 语句和参数 map，没有参数时返回 None
+    pub fn build(self) -> Result<(String, Option<AgType<Value>>), Status> {
+        if let Some(error) = self.error {
+            return Err(Status::invalid_argument(error));
+        }
+        let params = if self.params.is_empty() {
+            None
+        } else {
+            Some(AgType(Value::Object(self.params)))
+        };
+        Ok((self.clauses.join(" "), params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pilota::{AHashMap, FastStr};
+    use serde_json::{json, Value};
+
+    use super::{Condition, CypherQuery, EdgePattern, NodePattern, Pattern};
+    use crate::graph::{
+        search_node_cypher, search_origin_id_to_assigned_target_node_path_cypher, NodeType,
+    };
+
+    const HOSTILE: [&str; 3] = [
+        "O'Brien",
+        "$$) AS (v agtype); DROP TABLE user_property; --",
+        "x'}) DETACH DELETE n //",
+    ];
+
+    fn rendered(query: CypherQuery) -> (String, Value) {
+        let (cypher, params) = query.build().unwrap();
+        (cypher, params.map(|p| p.0).unwrap_or(Value::Null))
+    }
+
+    #[test]
+    fn hostile_values_are_bound_as_params() {
+        let query = CypherQuery::new()
+            .match_([
+                Pattern::node(NodePattern::new("n").property("name", HOSTILE[0])).to(
+                    EdgePattern::new().alias("r").property("name", HOSTILE[1]),
+                    NodePattern::new("m"),
+                ),
+            ])
+            .where_([Condition::property("m", "name", HOSTILE[2])])
+            .set("n", vec![("note".to_owned(), json!(HOSTILE[1]))])
+            .return_("n");
+        let (cypher, params) = rendered(query);
+        assert_eq!(
+            cypher,
+            "MATCH (n {name: $p0})-[r {name: $p1}]->(m) WHERE m.name = $p2 SET n.note = $p3 RETURN n"
+        );
+        assert_eq!(
+            params,
+            json!({"p0": HOSTILE[0], "p1": HOSTILE[1], "p2": HOSTILE[2], "p3": HOSTILE[1]})
+        );
+    }
+
+    #[test]
+    fn search_node_binds_name_and_properties() {
+        for value in HOSTILE {
+            let mut properties = AHashMap::default();
+            properties.insert(FastStr::from("org"), FastStr::from(value.to_owned()));
+            let query =
+                search_node_cypher(NodeType::User, Some(value), None, properties.clone()).unwrap();
+            let (cypher, params) = rendered(query);
+            assert_eq!(
+                cypher,
+                "MATCH (u: User {name: $p0}) WHERE u.org = $p1 RETURN u"
+            );
+            assert!(!cypher.contains(value));
+            assert_eq!(params, json!({"p0": value, "p1": value}));
+
+            let query = search_origin_id_to_assigned_target_node_path_cypher(
+                NodeType::User,
+                1,
+                NodeType::UserAttribute,
+                Some(value),
+                None,
+                properties,
+                false,
+            )
+            .unwrap();
+            let (cypher, params) = rendered(query);
+            assert!(!cypher.contains(value));
+            assert_eq!(params, json!({"p0": value, "p1": value}));
+        }
+    }
+
+    #[test]
+    fn build_rejects_non_identifiers() {
+        for ident in ["", "1n", "n m", "n'", "n}", "n`", "n:User", "$p0"] {
+            let queries = [
+                CypherQuery::new().match_([Pattern::node(NodePattern::new(ident))]),
+                CypherQuery::new().match_([Pattern::node(NodePattern::new("n").label(ident))]),
+                CypherQuery::new()
+                    .match_([Pattern::node(NodePattern::new("n").property(ident, "v"))]),
+                CypherQuery::new().match_([Pattern::node(NodePattern::new("n"))
+                    .to(EdgePattern::new().alias(ident), NodePattern::new("m"))]),
+                CypherQuery::new().match_([Pattern::node(NodePattern::new("n"))
+                    .to(EdgePattern::new().label(ident), NodePattern::new("m"))]),
+                CypherQuery::new().match_([Pattern::node(NodePattern::new("n")).to(
+                    EdgePattern::new().property(ident, "v"),
+                    NodePattern::new("m"),
+                )]),
+                CypherQuery::new().match_([Pattern::node(NodePattern::new("n")).path(ident)]),
+                CypherQuery::new().where_([Condition::id(ident, 1)]),
+                CypherQuery::new().where_([Condition::property("n", ident, "v")]),
+                CypherQuery::new().where_([Condition::label_in("n", &[ident])]),
+                CypherQuery::new().set("n", vec![(ident.to_owned(), json!("v"))]),
+                CypherQuery::new().delete(&[ident], true),
+            ];
+            for query in queries {
+                assert!(query.build().is_err(), "accepted {:?}", ident);
+            }
+        }
+    }
+}
@@ -0,0 +1,433 @@
+use apache_age::tokio::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use volo_grpc::Status;
+
+use super::access_right::{AccessRight, AccessRightSet};
+use super::agtype::rows_to;
+use super::cypher::{Condition, CypherQuery, EdgePattern, NodePattern, Pattern};
+use super::edge::{edge_type_condition, EdgeType};
+use super::engine::PolicyEngine;
+use super::prohibition::Prohibition;
+use super::{execute_query, NodeType, OPERATIONS};
+
+/// 一条有向边及其两端节点的标签
+#[derive(Debug, Clone, Deserialize)]
+pub struct EdgeRecord {
+    pub start_id: i64,
+    pub start_label: String,
+    pub end_id: i64,
+    pub end_label: String,
+    pub label: String,
+    #[serde(default)]
+    pub properties: Value,
+}
+
+impl EdgeRecord {
+    pub fn start_type(&self) -> Option<NodeType> {
+        NodeType::from_label(&self.start_label)
+    }
+
+    pub fn end_type(&self) -> Option<NodeType> {
+        NodeType::from_label(&self.end_label)
+    }
+
+    pub fn edge_type(&self) -> Option<EdgeType> {
+        EdgeType::from_label(&self.label)
+    }
+
+    /// 边的标签与两端节点类型都符合该类型
+    fn is(&self, edge_type: EdgeType) -> bool {
+        if self.edge_type() != Some(edge_type) {
+            return false;
+        }
+        match (self.start_type(), self.end_type()) {
+            (Some(start), Some(end)) => edge_type.connects(start, end),
+            _ => false,
+        }
+    }
+
+    pub fn is_assignment(&self) -> bool {
+        self.is(EdgeType::Assignment)
+    }
+
+    pub fn is_association(&self) -> bool {
+        self.is(EdgeType::Association)
+    }
+
+    pub fn is_prohibition(&self) -> bool {
+        self.edge_type() == Some(EdgeType::Prohibition)
+    }
+
+    pub fn operations(&self) -> AccessRightSet {
+        AccessRightSet::from_value(self.properties.get(OPERATIONS))
+    }
+
+    /// 未指定操作时只要存在关联即授权，否则要求关联的权限集中包含该操作
+    pub fn grants(&self, operation: Option<&str>) -> bool {
+        match operation {
+            None => true,
+            Some(operation) => self.operations().grants(operation),
+        }
+    }
+}
+
+/// 以 [`EdgeRecord`] 形式返回 `(origin)-[r]->(target)` 的 RETURN 表达式
+pub const EDGE_RECORD: &str = "{start_id: id(origin), start_label: label(origin), \
+     end_id: id(target), end_label: label(target), label: label(r), properties: properties(r)}";
+
+async fn fetch_edges(
+    client: &Client,
+    end: &str,
+    ids: &[i64],
+    edge_types: &[EdgeType],
+) -> Result<Vec<EdgeRecord>, Status> {
+    let mut conditions = vec![Condition::id_in(end, ids.to_vec())];
+    conditions.extend(edge_type_condition("r", edge_types));
+    let query = CypherQuery::new()
+        .match_([Pattern::node(NodePattern::new("origin"))
+            .to(EdgePattern::new().alias("r"), NodePattern::new("target"))])
+        .where_(conditions)
+        .return_(EDGE_RECORD);
+    let rows = execute_query(client, query).await?;
+    rows_to(&rows)
+}
+
+/// 查询一批节点的出边，`edge_types` 为空时不限定边的类型
+pub async fn fetch_out_edges(
+    client: &Client,
+    ids: &[i64],
+    edge_types: &[EdgeType],
+) -> Result<Vec<EdgeRecord>, Status> {
+    fetch_edges(client, "origin", ids, edge_types).await
+}
+
+/// 查询一批节点的入边，`edge_types` 为空时不限定边的类型
+pub async fn fetch_in_edges(
+    client: &Client,
+    ids: &[i64],
+    edge_types: &[EdgeType],
+) -> Result<Vec<EdgeRecord>, Status> {
+    fetch_edges(client, "target", ids, edge_types).await
+}
+
+/// 内存中的策略子图，只包含判定所需的节点和边
+#[derive(Debug, Default, Clone)]
+pub struct PolicySubgraph {
+    node_types: HashMap<i64, NodeType>,
+    out_edges: HashMap<i64, Vec<EdgeRecord>>,
+}
+
+impl PolicySubgraph {
+    pub fn node_type(&self, id: i64) -> Option<NodeType> {
+        self.node_types.get(&id).copied()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (i64, NodeType)> + '_ {
+        self.node_types
+            .iter()
+            .map(|(id, node_type)| (*id, *node_type))
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = &EdgeRecord> {
+        self.out_edges.values().flatten()
+    }
+
+    pub fn insert_node(&mut self, id: i64, node_type: NodeType) {
+        self.node_types.insert(id, node_type);
+    }
+
+    /// 删除节点及其出边和入边
+    pub fn remove_node(&mut self, id: i64) {
+        self.node_types.remove(&id);
+        self.out_edges.remove(&id);
+        for edges in self.out_edges.values_mut() {
+            edges.retain(|edge| edge.end_id != id);
+        }
+    }
+
+    /// 两端、标签和属性都相同的边
+    pub fn contains_edge(&self, edge: &EdgeRecord) -> bool {
+        self.out_edges.get(&edge.start_id).is_some_and(|edges| {
+            edges.iter().any(|e| {
+                e.end_id == edge.end_id && e.label == edge.label && e.properties == edge.properties
+            })
+        })
+    }
+
+    pub fn insert_edge(&mut self, edge: EdgeRecord) {
+        if let Some(start_type) = edge.start_type() {
+            self.node_types.insert(edge.start_id, start_type);
+        }
+        if let Some(end_type) = edge.end_type() {
+            self.node_types.insert(edge.end_id, end_type);
+        }
+        if !self.contains_edge(&edge) {
+            self.out_edges.entry(edge.start_id).or_default().push(edge);
+        }
+    }
+
+    /// 从起点出发逐层加载出边，只沿指派关系继续向上展开，关联边只记录不展开
+    pub async fn load(client: &Client, starts: &[i64]) -> Result<Self, Status> {
+        let mut graph = PolicySubgraph::default();
+        let mut visited: HashSet<i64> = starts.iter().copied().collect();
+        let mut frontier: Vec<i64> = visited.iter().copied().collect();
+        while !frontier.is_empty() {
+            let edges = fetch_out_edges(client, &frontier, &[]).await?;
+            frontier = Vec::new();
+            for edge in edges {
+                if edge.is_assignment() && visited.insert(edge.end_id) {
+                    frontier.push(edge.end_id);
+                }
+                graph.insert_edge(edge);
+            }
+        }
+        Ok(graph)
+    }
+
+    /// 沿指派关系可达的全部节点，包含起点本身
+    pub fn assigned_closure(&self, start: i64) -> HashSet<i64> {
+        let mut closure = HashSet::from([start]);
+        let mut stack = vec![start];
+        while let Some(id) = stack.pop() {
+            for edge in self.out_edges.get(&id).into_iter().flatten() {
+                if edge.is_assignment() && closure.insert(edge.end_id) {
+                    stack.push(edge.end_id);
+                }
+            }
+        }
+        closure
+    }
+
+    /// 沿指派关系从 start 到 end 的最短路径上的节点，包含两端
+    pub fn assignment_path(&self, start: i64, end: i64) -> Option<Vec<i64>> {
+        let mut parents: HashMap<i64, i64> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        let mut visited = HashSet::from([start]);
+        while let Some(id) = queue.pop_front() {
+            if id == end {
+                let mut path = vec![end];
+                let mut current = end;
+                while let Some(parent) = parents.get(&current) {
+                    path.push(*parent);
+                    current = *parent;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for edge in self.out_edges.get(&id).into_iter().flatten() {
+                if edge.is_assignment() && visited.insert(edge.end_id) {
+                    parents.insert(edge.end_id, id);
+                    queue.push_back(edge.end_id);
+                }
+            }
+        }
+        None
+    }
+
+    fn policy_classes(&self, closure: &HashSet<i64>) -> HashSet<i64> {
+        closure
+            .iter()
+            .copied()
+            .filter(|id| self.node_type(*id) == Some(NodeType::PolicyClass))
+            .collect()
+    }
+
+    pub fn associations(&self) -> impl Iterator<Item = &EdgeRecord> {
+        self.out_edges
+            .values()
+            .flatten()
+            .filter(|edge| edge.is_association())
+    }
+
+    pub fn prohibitions(&self) -> Vec<Prohibition> {
+        Prohibition::from_edges(self.out_edges.values().flatten())
+    }
+
+    /// 作用于用户（或其所属 UA）且覆盖该资源的禁止关系中，被禁止的操作
+    pub fn prohibited_operations(&self, user_id: i64, resource_id: i64) -> AccessRightSet {
+        let user_closure = self.assigned_closure(user_id);
+        let resource_closure = self.assigned_closure(resource_id);
+        self.prohibitions()
+            .iter()
+            .filter(|p| user_closure.contains(&p.subject_id) && p.covers(&resource_closure))
+            .fold(AccessRightSet::new(), |denied, p| {
+                denied.union(&p.operations)
+            })
+    }
+
+    /// 关联授予的操作减去禁止的操作
+    ///
+    /// 未指定操作时，只要存在未被禁止的授权操作即可访问；没有权限集的关联不授予任何操作。
+    pub fn is_permitted(&self, user_id: i64, resource_id: i64, operation: Option<&str>) -> bool {
+        let denied = self.prohibited_operations(user_id, resource_id);
+        match operation {
+            Some(operation) => {
+                !denied.grants(operation) && self.is_granted(user_id, resource_id, Some(operation))
+            }
+            None => {
+                if denied.grants("*") || !self.is_granted(user_id, resource_id, None) {
+                    return false;
+                }
+                let user_closure = self.assigned_closure(user_id);
+                let resource_closure = self.assigned_closure(resource_id);
+                self.associations()
+                    .filter(|edge| {
+                        user_closure.contains(&edge.start_id)
+                            && resource_closure.contains(&edge.end_id)
+                    })
+                    .flat_map(|edge| edge.operations().iter().cloned().collect::<Vec<_>>())
+                    .any(|right| {
+                        (right == AccessRight::All || !denied.grants(right.as_str()))
+                            && self.is_granted(user_id, resource_id, Some(right.as_str()))
+                    })
+            }
+        }
+    }
+
+    /// NGAC 判定：用户与资源共同所属的每个策略类中，都要有一条授予该操作的关联
+    /// UA→OA，其中 UA 由用户指派可达、OA 由资源指派可达，且两端都包含在该策略类中。
+    /// 没有共同的策略类时拒绝。不考虑禁止关系。
+    pub fn is_granted(&self, user_id: i64, resource_id: i64, operation: Option<&str>) -> bool {
+        let user_closure = self.assigned_closure(user_id);
+        let resource_closure = self.assigned_closure(resource_id);
+        let policy_classes: HashSet<i64> = self
+            .policy_classes(&user_closure)
+            .intersection(&self.policy_classes(&resource_closure))
+            .copied()
+            .collect();
+        if policy_classes.is_empty() {
+            return false;
+        }
+
+        let granting: Vec<(HashSet<i64>, HashSet<i64>)> = self
+            .associations()
+            .filter(|edge| {
+                user_closure.contains(&edge.start_id)
+                    && resource_closure.contains(&edge.end_id)
+                    && edge.grants(operation)
+            })
+            .map(|edge| {
+                (
+                    self.assigned_closure(edge.start_id),
+                    self.assigned_closure(edge.end_id),
+                )
+            })
+            .collect();
+
+        policy_classes.iter().all(|pc| {
+            granting
+                .iter()
+                .any(|(ua_closure, oa_closure)| ua_closure.contains(pc) && oa_closure.contains(pc))
+        })
+    }
+}
+
+/// 校验两端节点类型后做 NGAC 判定，图中不存在的节点按无权限处理
+pub fn decide(
+    graph: &PolicySubgraph,
+    user_id: i64,
+    resource_id: i64,
+    operation: Option<&str>,
+) -> Result<bool, Status> {
+    if graph
+        .node_type(user_id)
+        .is_some_and(|t| t != NodeType::User)
+    {
+        return Err(Status::invalid_argument("user_id is not a user node!"));
+    }
+    if graph
+        .node_type(resource_id)
+        .is_some_and(|t| t != NodeType::Object && t != NodeType::ObjectAttribute)
+    {
+        return Err(Status::invalid_argument(
+            "resource_id is not an object or object attribute node!",
+        ));
+    }
+    Ok(graph.is_permitted(user_id, resource_id, operation))
+}
+
+/// NGAC 判定：内存策略图已加载时直接判定，否则加载用户和资源向上的子图后判定
+pub async fn check_permission(
+    client: &Client,
+    user_id: i64,
+    resource_id: i64,
+    operation: Option<&str>,
+) -> Result<bool, Status> {
+    if let Some(decision) = PolicyEngine::decide(user_id, resource_id, operation) {
+        return decision;
+    }
+    let graph = PolicySubgraph::load(client, &[user_id, resource_id]).await?;
+    decide(&graph, user_id, resource_id, operation)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use serde_json::{json, Value};
+
+    use super::{EdgeRecord, PolicySubgraph};
+    use crate::graph::{NodeType, ASSIGNMENT, ASSOCIATION};
+
+    pub(crate) fn edge(
+        start: (NodeType, i64),
+        label: &str,
+        end: (NodeType, i64),
+        properties: Value,
+    ) -> EdgeRecord {
+        EdgeRecord {
+            start_id: start.1,
+            start_label: start.0.fmt_full(),
+            end_id: end.1,
+            end_label: end.0.fmt_full(),
+            label: label.to_owned(),
+            properties,
+        }
+    }
+
+    pub(crate) fn assign(start: (NodeType, i64), end: (NodeType, i64)) -> EdgeRecord {
+        edge(start, ASSIGNMENT, end, json!({}))
+    }
+
+    /// u1 → ua2 → pc10 ← oa4 ← o3，ua2 -[properties]-> oa4
+    fn graph(association: Value) -> PolicySubgraph {
+        let mut graph = PolicySubgraph::default();
+        for edge in [
+            assign((NodeType::User, 1), (NodeType::UserAttribute, 2)),
+            assign((NodeType::UserAttribute, 2), (NodeType::PolicyClass, 10)),
+            assign((NodeType::Object, 3), (NodeType::ObjectAttribute, 4)),
+            assign((NodeType::ObjectAttribute, 4), (NodeType::PolicyClass, 10)),
+            edge(
+                (NodeType::UserAttribute, 2),
+                ASSOCIATION,
+                (NodeType::ObjectAttribute, 4),
+                association,
+            ),
+        ] {
+            graph.insert_edge(edge);
+        }
+        graph
+    }
+
+    #[test]
+    fn association_without_operations_grants_nothing() {
+        for properties in [json!({}), json!({"operations": []})] {
+            let graph = graph(properties);
+            assert!(!graph.is_permitted(1, 3, None));
+            assert!(!graph.is_permitted(1, 3, Some("read")));
+            assert!(!graph.is_permitted(1, 3, Some("*")));
+        }
+    }
+
+    #[test]
+    fn association_grants_its_operations() {
+        let graph = graph(json!({"operations": ["read"]}));
+        assert!(graph.is_permitted(1, 3, None));
+        assert!(graph.is_permitted(1, 3, Some("read")));
+        assert!(!graph.is_permitted(1, 3, Some("write")));
+
+        let graph = self::graph(json!({"operations": ["*"]}));
+        assert!(graph.is_permitted(1, 3, None));
+        assert!(graph.is_permitted(1, 3, Some("write")));
+    }
+}
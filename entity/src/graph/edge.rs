@@ -0,0 +1,156 @@
+use apache_age::tokio::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use volo_grpc::Status;
+
+use super::access_right::AccessRightSet;
+use super::agtype::rows_to;
+use super::cypher::{Condition, CypherQuery, EdgePattern, NodePattern, Pattern};
+use super::notify::{notify_changes, ChangeKind, GraphChange};
+use super::{execute_query, NodeType, ASSIGNMENT, ASSOCIATION, OPERATIONS, PROHIBITION};
+
+/// 边的类型，与 AGE 中的边标签一一对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EdgeType {
+    /// U→UA、UA→UA、UA→PC、O→OA、OA→OA、OA→PC
+    Assignment,
+    /// UA→OA，携带访问权限集
+    Association,
+    /// U/UA→OA，见 [`super::prohibition`]
+    Prohibition,
+}
+
+impl EdgeType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EdgeType::Assignment => ASSIGNMENT,
+            EdgeType::Association => ASSOCIATION,
+            EdgeType::Prohibition => PROHIBITION,
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<EdgeType> {
+        match label {
+            ASSIGNMENT => Some(EdgeType::Assignment),
+            ASSOCIATION => Some(EdgeType::Association),
+            PROHIBITION => Some(EdgeType::Prohibition),
+            _ => None,
+        }
+    }
+
+    /// 两端节点类型之间该类型的边是否合法
+    pub fn connects(&self, origin: NodeType, target: NodeType) -> bool {
+        match self {
+            EdgeType::Assignment => origin.can_assign_to(&target),
+            EdgeType::Association => origin.can_associate_with(&target),
+            EdgeType::Prohibition => {
+                matches!(origin, NodeType::User | NodeType::UserAttribute)
+                    && target == NodeType::ObjectAttribute
+            }
+        }
+    }
+
+    /// 两端节点类型对应的指派或关联边类型
+    pub fn between(origin: NodeType, target: NodeType) -> Option<EdgeType> {
+        [EdgeType::Assignment, EdgeType::Association]
+            .into_iter()
+            .find(|edge_type| edge_type.connects(origin, target))
+    }
+}
+
+/// AGE 返回的边，`RETURN r` 的结果可直接反序列化
+#[derive(Debug, Clone, Deserialize)]
+pub struct Edge {
+    pub id: i64,
+    #[serde(rename = "label")]
+    pub edge_type: EdgeType,
+    pub start_id: i64,
+    pub end_id: i64,
+    #[serde(default)]
+    pub properties: Value,
+}
+
+impl Edge {
+    pub fn operations(&self) -> AccessRightSet {
+        AccessRightSet::from_value(self.properties.get(OPERATIONS))
+    }
+}
+
+/// 限定边类型的条件，类型为空时不限定
+pub fn edge_type_condition(alias: &str, edge_types: &[EdgeType]) -> Option<Condition> {
+    if edge_types.is_empty() {
+        None
+    } else {
+        let labels: Vec<&str> = edge_types.iter().map(EdgeType::label).collect();
+        Some(Condition::label_in(alias, &labels))
+    }
+}
+
+/// 按类型和端点查询边，参数为 None 时不限定
+pub async fn list_edges(
+    client: &Client,
+    edge_types: &[EdgeType],
+    origin_id: Option<i64>,
+    target_id: Option<i64>,
+) -> Result<Vec<Edge>, Status> {
+    let mut conditions: Vec<Condition> = edge_type_condition("r", edge_types).into_iter().collect();
+    if let Some(origin_id) = origin_id {
+        conditions.push(Condition::id("origin", origin_id));
+    }
+    if let Some(target_id) = target_id {
+        conditions.push(Condition::id("target", target_id));
+    }
+    let query = CypherQuery::new()
+        .match_([Pattern::node(NodePattern::new("origin"))
+            .to(EdgePattern::new().alias("r"), NodePattern::new("target"))])
+        .where_(conditions)
+        .return_("r");
+    let rows = execute_query(client, query).await?;
+    rows_to(&rows)
+}
+
+#[derive(Deserialize)]
+struct MigratedEdge {
+    start_id: i64,
+    end_id: i64,
+}
+
+/// 把旧版本统一标记为 `Association` 的指派边改为 `Assignment` 标签
+///
+/// AGE 不能修改边的标签，以新建后删除的方式替换；旧指派边上没有属性。返回迁移的边数。
+pub async fn migrate_assignment_labels(client: &Client) -> Result<usize, Status> {
+    let query = CypherQuery::new()
+        .match_([Pattern::node(NodePattern::new("origin")).to(
+            EdgePattern::new().alias("r").label(ASSOCIATION),
+            NodePattern::new("target"),
+        )])
+        .where_([Condition::Raw(
+            "NOT (label(origin) = 'UserAttribute' AND label(target) = 'ObjectAttribute')"
+                .to_owned(),
+        )])
+        .create([Pattern::node(NodePattern::new("origin")).to(
+            EdgePattern::new().alias("n").label(ASSIGNMENT),
+            NodePattern::new("target"),
+        )])
+        .delete(&["r"], false)
+        .return_("{start_id: id(origin), end_id: id(target)}");
+    let migrated: Vec<MigratedEdge> = rows_to(&execute_query(client, query).await?)?;
+    let changes = migrated.iter().flat_map(|edge| {
+        [
+            GraphChange::edge(
+                ChangeKind::Deleted,
+                EdgeType::Association,
+                edge.start_id,
+                edge.end_id,
+            ),
+            GraphChange::edge(
+                ChangeKind::Created,
+                EdgeType::Assignment,
+                edge.start_id,
+                edge.end_id,
+            ),
+        ]
+    });
+    notify_changes(client, changes).await?;
+    Ok(migrated.len())
+}
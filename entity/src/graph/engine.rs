@@ -0,0 +1,344 @@
+use apache_age::tokio::Client;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use tokio::sync::Mutex;
+use volo_grpc::Status;
+
+use super::decision::{decide, fetch_in_edges, fetch_out_edges, EdgeRecord, PolicySubgraph};
+use super::notify::GraphChange;
+use super::render::GraphView;
+use super::{search_node_types, NodeType};
+
+/// 内存策略图与数据库的差异
+///
+/// missing 为数据库中有而内存中没有的，stale 为内存中有而数据库中已没有的；
+/// 节点类型不一致时同时出现在两侧。
+#[derive(Debug, Clone, Default)]
+pub struct ConsistencyReport {
+    pub missing_nodes: Vec<(i64, NodeType)>,
+    pub stale_nodes: Vec<(i64, NodeType)>,
+    pub missing_edges: Vec<EdgeRecord>,
+    pub stale_edges: Vec<EdgeRecord>,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.missing_nodes.is_empty()
+            && self.stale_nodes.is_empty()
+            && self.missing_edges.is_empty()
+            && self.stale_edges.is_empty()
+    }
+
+    fn diff(memory: &PolicySubgraph, database: &PolicySubgraph) -> Self {
+        let mut report = ConsistencyReport {
+            missing_nodes: database
+                .nodes()
+                .filter(|(id, node_type)| memory.node_type(*id) != Some(*node_type))
+                .collect(),
+            stale_nodes: memory
+                .nodes()
+                .filter(|(id, node_type)| database.node_type(*id) != Some(*node_type))
+                .collect(),
+            missing_edges: database
+                .edges()
+                .filter(|edge| !memory.contains_edge(edge))
+                .cloned()
+                .collect(),
+            stale_edges: memory
+                .edges()
+                .filter(|edge| !database.contains_edge(edge))
+                .cloned()
+                .collect(),
+        };
+        report.missing_nodes.sort();
+        report.stale_nodes.sort();
+        report
+    }
+}
+
+/// 用从数据库读取的节点类型及其出边、入边替换内存中这些节点，不在 node_types 中的节点已被删除
+fn refresh(
+    graph: &mut PolicySubgraph,
+    ids: &[i64],
+    node_types: HashMap<i64, NodeType>,
+    edges: Vec<EdgeRecord>,
+) {
+    for id in ids {
+        graph.remove_node(*id);
+    }
+    for (id, node_type) in node_types {
+        graph.insert_node(id, node_type);
+    }
+    for edge in edges {
+        graph.insert_edge(edge);
+    }
+}
+
+/// 内存中的完整策略图，启动时从 ngac 图加载，由本服务的变更和图变更通知同步
+///
+/// 未加载（或同步失败被作废）时所有判定回退到数据库查询。
+pub struct PolicyEngine;
+
+static POLICY_GRAPH: OnceLock<RwLock<Option<PolicySubgraph>>> = OnceLock::new();
+
+/// 加载和同步在这把锁内读取数据库并写入内存，后开始的同步总能读到更新的数据，
+/// 先开始、后完成的同步不会用旧数据覆盖它
+static SYNC_LOCK: Mutex<()> = Mutex::const_new(());
+
+impl PolicyEngine {
+    fn graph() -> &'static RwLock<Option<PolicySubgraph>> {
+        POLICY_GRAPH.get_or_init(|| RwLock::new(None))
+    }
+
+    fn replace(graph: Option<PolicySubgraph>) {
+        *PolicyEngine::graph()
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = graph;
+    }
+
+    async fn fetch(client: &Client) -> Result<PolicySubgraph, Status> {
+        let view = GraphView::full(client).await?;
+        let mut graph = PolicySubgraph::default();
+        for node in view.nodes.values() {
+            if let Some(node_type) = node.node_type() {
+                graph.insert_node(node.id, node_type);
+            }
+        }
+        for edge in view.edges {
+            graph.insert_edge(edge);
+        }
+        Ok(graph)
+    }
+
+    /// 从数据库加载整个策略图，替换内存中的图，返回节点数量
+    pub async fn load(client: &Client) -> Result<usize, Status> {
+        let _sync = SYNC_LOCK.lock().await;
+        let graph = PolicyEngine::fetch(client).await?;
+        let count = graph.nodes().count();
+        PolicyEngine::replace(Some(graph));
+        Ok(count)
+    }
+
+    pub fn is_loaded() -> bool {
+        PolicyEngine::graph()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+    }
+
+    /// 作废内存中的图，之后的判定回退到数据库，直到重新加载
+    pub fn invalidate() {
+        PolicyEngine::replace(None);
+    }
+
+    /// 用内存中的图做判定，未加载时返回 None
+    pub fn decide(
+        user_id: i64,
+        resource_id: i64,
+        operation: Option<&str>,
+    ) -> Option<Result<bool, Status>> {
+        PolicyEngine::graph()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|graph| decide(graph, user_id, resource_id, operation))
+    }
+
+    /// 变更提交后，从数据库重新读取这些节点的类型及其全部出边和入边
+    ///
+    /// 已删除的节点从内存中移除。同步失败时作废内存中的图，避免基于过期的图做判定；
+    /// 变更本身已经生效，因此不返回错误。未加载时什么都不做。
+    pub async fn sync(client: &Client, ids: &[i64]) {
+        if ids.is_empty() || !PolicyEngine::is_loaded() {
+            return;
+        }
+        let _sync = SYNC_LOCK.lock().await;
+        let fetched = async {
+            let node_types = search_node_types(client, ids).await?;
+            let mut edges = fetch_out_edges(client, ids, &[]).await?;
+            edges.extend(fetch_in_edges(client, ids, &[]).await?);
+            Ok::<_, Status>((node_types, edges))
+        }
+        .await;
+
+        let mut guard = PolicyEngine::graph()
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        let Some(graph) = guard.as_mut() else {
+            return;
+        };
+        let Ok((node_types, edges)) = fetched else {
+            *guard = None;
+            return;
+        };
+        refresh(graph, ids, node_types, edges);
+    }
+
+    /// 按收到的图变更通知同步涉及的节点，用于同步其他副本写入的变更
+    pub async fn apply(client: &Client, change: &GraphChange) {
+        PolicyEngine::sync(client, &change.ids()).await;
+    }
+
+    /// 比较内存中的图与数据库，repair 为 true 时用数据库中的图替换内存中的图
+    ///
+    /// 未加载时报告中的全部节点和边都为 missing。
+    pub async fn check_consistency(
+        client: &Client,
+        repair: bool,
+    ) -> Result<ConsistencyReport, Status> {
+        let _sync = SYNC_LOCK.lock().await;
+        let database = PolicyEngine::fetch(client).await?;
+        let report = {
+            let guard = PolicyEngine::graph()
+                .read()
+                .unwrap_or_else(|e| e.into_inner());
+            let empty = PolicySubgraph::default();
+            ConsistencyReport::diff(guard.as_ref().unwrap_or(&empty), &database)
+        };
+        if repair {
+            PolicyEngine::replace(Some(database));
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    use super::{refresh, ConsistencyReport};
+    use crate::graph::decision::tests::{assign, edge};
+    use crate::graph::decision::{EdgeRecord, PolicySubgraph};
+    use crate::graph::{NodeType, ASSOCIATION};
+
+    const U1: (NodeType, i64) = (NodeType::User, 1);
+    const UA2: (NodeType, i64) = (NodeType::UserAttribute, 2);
+    const O3: (NodeType, i64) = (NodeType::Object, 3);
+    const OA4: (NodeType, i64) = (NodeType::ObjectAttribute, 4);
+    const PC10: (NodeType, i64) = (NodeType::PolicyClass, 10);
+
+    fn associate(operations: &[&str]) -> EdgeRecord {
+        edge(UA2, ASSOCIATION, OA4, json!({ "operations": operations }))
+    }
+
+    fn graph(edges: Vec<EdgeRecord>) -> PolicySubgraph {
+        let mut graph = PolicySubgraph::default();
+        for edge in edges {
+            graph.insert_edge(edge);
+        }
+        graph
+    }
+
+    /// u1 → ua2 → pc10 ← oa4 ← o3，ua2 -[read]-> oa4
+    fn policy() -> PolicySubgraph {
+        graph(vec![
+            assign(U1, UA2),
+            assign(UA2, PC10),
+            assign(O3, OA4),
+            assign(OA4, PC10),
+            associate(&["read"]),
+        ])
+    }
+
+    fn pairs(edges: &[EdgeRecord]) -> Vec<(i64, i64)> {
+        let mut pairs: Vec<(i64, i64)> = edges.iter().map(|e| (e.start_id, e.end_id)).collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn identical_graphs_are_consistent() {
+        assert!(ConsistencyReport::diff(&policy(), &policy()).is_consistent());
+    }
+
+    #[test]
+    fn missing_and_stale_nodes_and_edges() {
+        let mut memory = policy();
+        memory.insert_edge(assign((NodeType::Object, 5), OA4));
+        let mut database = policy();
+        database.remove_node(3);
+
+        let report = ConsistencyReport::diff(&memory, &database);
+        assert!(report.missing_nodes.is_empty());
+        assert!(report.missing_edges.is_empty());
+        assert_eq!(
+            report.stale_nodes,
+            vec![(3, NodeType::Object), (5, NodeType::Object)]
+        );
+        assert_eq!(pairs(&report.stale_edges), vec![(3, 4), (5, 4)]);
+
+        let report = ConsistencyReport::diff(&database, &memory);
+        assert_eq!(
+            report.missing_nodes,
+            vec![(3, NodeType::Object), (5, NodeType::Object)]
+        );
+        assert_eq!(pairs(&report.missing_edges), vec![(3, 4), (5, 4)]);
+        assert!(report.stale_nodes.is_empty());
+        assert!(report.stale_edges.is_empty());
+    }
+
+    #[test]
+    fn changed_node_type_and_edge_properties_appear_on_both_sides() {
+        let memory = policy();
+        let mut database = policy();
+        database.insert_node(3, NodeType::ObjectAttribute);
+        database.remove_node(2);
+        for edge in [
+            assign(U1, UA2),
+            assign(UA2, PC10),
+            associate(&["read", "write"]),
+        ] {
+            database.insert_edge(edge);
+        }
+
+        let report = ConsistencyReport::diff(&memory, &database);
+        assert_eq!(report.missing_nodes, vec![(3, NodeType::ObjectAttribute)]);
+        assert_eq!(report.stale_nodes, vec![(3, NodeType::Object)]);
+        assert_eq!(pairs(&report.missing_edges), vec![(2, 4)]);
+        assert_eq!(
+            report.missing_edges[0].operations(),
+            associate(&["read", "write"]).operations()
+        );
+        assert_eq!(pairs(&report.stale_edges), vec![(2, 4)]);
+        assert_eq!(
+            report.stale_edges[0].operations(),
+            associate(&["read"]).operations()
+        );
+    }
+
+    #[test]
+    fn refresh_removes_deleted_nodes_and_their_edges() {
+        let mut memory = policy();
+        refresh(&mut memory, &[2], HashMap::new(), Vec::new());
+
+        // ua2 删除后 u1 仍在，只是没有边了
+        let mut database = graph(vec![assign(O3, OA4), assign(OA4, PC10)]);
+        database.insert_node(1, NodeType::User);
+        assert!(ConsistencyReport::diff(&memory, &database).is_consistent());
+        assert_eq!(memory.node_type(2), None);
+    }
+
+    #[test]
+    fn refresh_replaces_changed_edges() {
+        let mut memory = policy();
+        let database = graph(vec![
+            assign(U1, UA2),
+            assign(UA2, PC10),
+            assign(O3, OA4),
+            assign(OA4, PC10),
+            associate(&["read", "write"]),
+        ]);
+        refresh(
+            &mut memory,
+            &[2],
+            HashMap::from([(2, NodeType::UserAttribute)]),
+            vec![
+                assign(UA2, PC10),
+                associate(&["read", "write"]),
+                assign(U1, UA2),
+            ],
+        );
+        assert!(ConsistencyReport::diff(&memory, &database).is_consistent());
+    }
+}
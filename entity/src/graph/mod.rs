@@ -0,0 +1,1217 @@
+use apache_age::{tokio::{AgeClient, Client}, Vertex};
+use pilota::{AHashMap, FastStr};
+use sonic_rs::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use std::collections::HashMap;
+use std::fmt::Display;
+use volo_grpc::{Code, Status};
+
+pub mod access_right;
+pub mod agtype;
+pub mod cypher;
+pub mod decision;
+pub mod edge;
+pub mod engine;
+pub mod notify;
+pub mod obligation;
+pub mod policy;
+pub mod prohibition;
+pub mod render;
+pub mod review;
+
+use access_right::AccessRightSet;
+use agtype::rows_to;
+use cypher::{Condition, CypherQuery, EdgePattern, NodePattern, Pattern};
+use decision::{fetch_in_edges, fetch_out_edges, EdgeRecord, PolicySubgraph, EDGE_RECORD};
+use edge::EdgeType;
+use notify::{notify_changes, ChangeKind, GraphChange};
+
+pub const GRAPH_NAME: &str = "ngac";
+pub const CREATE: &str = "CREATE";
+pub const MATCH: &str = "MATCH";
+pub const SET: &str = "SET";
+pub const WHERE: &str = "WHERE";
+pub const AND: &str = "AND";
+pub const RETURN: &str = "RETURN";
+/// 指派边的标签
+pub const ASSIGNMENT: &str = "Assignment";
+/// UA→OA 关联边的标签
+pub const ASSOCIATION: &str = "Association";
+/// 禁止关系边的标签
+pub const PROHIBITION: &str = "Prohibition";
+/// 关联边上保存访问权限集的属性名
+pub const OPERATIONS: &str = "operations";
+/// 对象属性上声明资源类型的属性名，决定关联和禁止关系可用的自定义操作
+pub const RESOURCE_TYPE: &str = "resource_type";
+
+pub struct OpenCypherFunc;
+
+impl OpenCypherFunc {
+    pub fn id(node: &str) -> String {
+        format!("id({})", node)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct User {
+    pub name: String,
+    #[serde(flatten)]
+    pub properties: AHashMap<FastStr, FastStr>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserAttribute {
+    pub name: String,
+    #[serde(flatten)]
+    pub properties: AHashMap<FastStr, FastStr>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Object {
+    pub name: String,
+    #[serde(flatten)]
+    pub properties: AHashMap<FastStr, FastStr>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ObjectAttribute {
+    pub name: String,
+    #[serde(flatten)]
+    pub properties: AHashMap<FastStr, FastStr>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PolicyClass {
+    pub name: String,
+    #[serde(flatten)]
+    pub properties: AHashMap<FastStr, FastStr>,
+}
+
+pub enum Assignment {
+    U2UA((i64, i64)),
+    UA2UA((i64, i64)),
+    UA2OA((i64, i64), AccessRightSet),
+    UA2PC((i64, i64)),
+    O2OA((i64, i64)),
+    OA2OA((i64, i64)),
+    OA2PC((i64, i64)),
+}
+
+impl Assignment {
+    /// 按两端节点类型选出合法的 NGAC 关系，不合法时返回 None；权限集只用于 UA→OA 关联
+    pub fn from_node_types(
+        origin_node_type: NodeType,
+        origin_id: i64,
+        target_node_type: NodeType,
+        target_id: i64,
+        operations: AccessRightSet,
+    ) -> Option<Assignment> {
+        let ids = (origin_id, target_id);
+        match (origin_node_type, target_node_type) {
+            (NodeType::User, NodeType::UserAttribute) => Some(Assignment::U2UA(ids)),
+            (NodeType::UserAttribute, NodeType::UserAttribute) => Some(Assignment::UA2UA(ids)),
+            (NodeType::UserAttribute, NodeType::ObjectAttribute) => {
+                Some(Assignment::UA2OA(ids, operations))
+            }
+            (NodeType::UserAttribute, NodeType::PolicyClass) => Some(Assignment::UA2PC(ids)),
+            (NodeType::Object, NodeType::ObjectAttribute) => Some(Assignment::O2OA(ids)),
+            (NodeType::ObjectAttribute, NodeType::ObjectAttribute) => Some(Assignment::OA2OA(ids)),
+            (NodeType::ObjectAttribute, NodeType::PolicyClass) => Some(Assignment::OA2PC(ids)),
+            _ => None,
+        }
+    }
+
+    /// 边的起点和终点：(节点类型, 节点 id)
+    pub fn endpoints(&self) -> ((NodeType, i64), (NodeType, i64)) {
+        let (origin_node_type, target_node_type, (origin_id, target_id)) = match self {
+            Assignment::U2UA(ids) => (NodeType::User, NodeType::UserAttribute, ids),
+            Assignment::UA2UA(ids) => (NodeType::UserAttribute, NodeType::UserAttribute, ids),
+            Assignment::UA2OA(ids, _) => (NodeType::UserAttribute, NodeType::ObjectAttribute, ids),
+            Assignment::UA2PC(ids) => (NodeType::UserAttribute, NodeType::PolicyClass, ids),
+            Assignment::O2OA(ids) => (NodeType::Object, NodeType::ObjectAttribute, ids),
+            Assignment::OA2OA(ids) => (NodeType::ObjectAttribute, NodeType::ObjectAttribute, ids),
+            Assignment::OA2PC(ids) => (NodeType::ObjectAttribute, NodeType::PolicyClass, ids),
+        };
+        ((origin_node_type, *origin_id), (target_node_type, *target_id))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum NodeType {
+    User,
+    UserAttribute,
+    Object,
+    ObjectAttribute,
+    PolicyClass,
+}
+
+impl Display for NodeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeType::User => write!(f, "u"),
+            NodeType::UserAttribute => write!(f, "ua"),
+            NodeType::Object => write!(f, "o"),
+            NodeType::ObjectAttribute => write!(f, "oa"),
+            NodeType::PolicyClass => write!(f, "pc"),
+        }
+    }
+}
+
+impl NodeType {
+    fn fmt_full(&self) -> String {
+        match self {
+            NodeType::User => "User".to_owned(),
+            NodeType::UserAttribute => "UserAttribute".to_owned(),
+            NodeType::Object => "Object".to_owned(),
+            NodeType::ObjectAttribute => "ObjectAttribute".to_owned(),
+            NodeType::PolicyClass => "PolicyClass".to_owned(),
+        }
+    }
+
+    /// 由顶点标签还原节点类型
+    pub fn from_label(label: &str) -> Option<NodeType> {
+        match label {
+            "User" => Some(NodeType::User),
+            "UserAttribute" => Some(NodeType::UserAttribute),
+            "Object" => Some(NodeType::Object),
+            "ObjectAttribute" => Some(NodeType::ObjectAttribute),
+            "PolicyClass" => Some(NodeType::PolicyClass),
+            _ => None,
+        }
+    }
+
+    /// NGAC 允许的指派：U→UA、UA→UA、UA→PC、O→OA、OA→OA、OA→PC
+    pub fn can_assign_to(&self, target: &NodeType) -> bool {
+        matches!(
+            (self, target),
+            (NodeType::User, NodeType::UserAttribute)
+                | (NodeType::UserAttribute, NodeType::UserAttribute)
+                | (NodeType::UserAttribute, NodeType::PolicyClass)
+                | (NodeType::Object, NodeType::ObjectAttribute)
+                | (NodeType::ObjectAttribute, NodeType::ObjectAttribute)
+                | (NodeType::ObjectAttribute, NodeType::PolicyClass)
+        )
+    }
+
+    /// NGAC 关联：UA→OA
+    pub fn can_associate_with(&self, target: &NodeType) -> bool {
+        matches!(
+            (self, target),
+            (NodeType::UserAttribute, NodeType::ObjectAttribute)
+        )
+    }
+}
+
+pub enum NodeTypeObject {
+    User(User),
+    UserAttribute(UserAttribute),
+    Object(Object),
+    ObjectAttribute(ObjectAttribute),
+    PolicyClass(PolicyClass),
+}
+
+impl NodeTypeObject {
+    pub fn new(
+        node_type: NodeType,
+        name: String,
+        properties: AHashMap<FastStr, FastStr>,
+    ) -> NodeTypeObject {
+        match node_type {
+            NodeType::User => NodeTypeObject::User(User { name, properties }),
+            NodeType::UserAttribute => {
+                NodeTypeObject::UserAttribute(UserAttribute { name, properties })
+            }
+            NodeType::Object => NodeTypeObject::Object(Object { name, properties }),
+            NodeType::ObjectAttribute => {
+                NodeTypeObject::ObjectAttribute(ObjectAttribute { name, properties })
+            }
+            NodeType::PolicyClass => NodeTypeObject::PolicyClass(PolicyClass { name, properties }),
+        }
+    }
+}
+
+pub enum VertexTypeObject {
+    User(Vertex<User>),
+    UserAttribute(Vertex<UserAttribute>),
+    Object(Vertex<Object>),
+    ObjectAttribute(Vertex<ObjectAttribute>),
+    PolicyClass(Vertex<PolicyClass>),
+}
+
+impl VertexTypeObject {
+    pub fn id(&self) -> i64 {
+        (match self {
+            VertexTypeObject::User(v) => v.id(),
+            VertexTypeObject::UserAttribute(v) => v.id(),
+            VertexTypeObject::Object(v) => v.id(),
+            VertexTypeObject::ObjectAttribute(v) => v.id(),
+            VertexTypeObject::PolicyClass(v) => v.id(),
+        }) as i64
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            VertexTypeObject::User(v) => &v.properties().name,
+            VertexTypeObject::UserAttribute(v) => &v.properties().name,
+            VertexTypeObject::Object(v) => &v.properties().name,
+            VertexTypeObject::ObjectAttribute(v) => &v.properties().name,
+            VertexTypeObject::PolicyClass(v) => &v.properties().name,
+        }
+    }
+}
+
+/// 同一连接上的事务；由 [`Transaction::savepoint`] 得到的是嵌套的 SAVEPOINT
+///
+/// 实现了 `Deref<Target = Client>`，需要 `&Client` 的地方直接传 `&tx`。
+/// 未提交或回滚就被丢弃（包括 future 被取消）时排入 ROLLBACK，连接不会带着未结束的事务回到连接池。
+pub struct Transaction<'a> {
+    client: &'a Client,
+    savepoint: Option<String>,
+    depth: usize,
+    done: bool,
+}
+
+impl<'a> Transaction<'a> {
+    /// 在连接上执行 BEGIN
+    pub async fn begin(client: &'a Client) -> Result<Transaction<'a>, Status> {
+        client
+            .batch_execute("BEGIN")
+            .await
+            .map_err(|e| Status::from_error(Box::new(e)))?;
+        Ok(Transaction {
+            client,
+            savepoint: None,
+            depth: 0,
+            done: false,
+        })
+    }
+
+    /// 在当前事务中开启一层 SAVEPOINT，回滚时只撤销这一层
+    pub async fn savepoint(&self) -> Result<Transaction<'_>, Status> {
+        let depth = self.depth + 1;
+        let name = format!("ngac_savepoint_{}", depth);
+        self.client
+            .batch_execute(&format!("SAVEPOINT {}", name))
+            .await
+            .map_err(|e| Status::from_error(Box::new(e)))?;
+        Ok(Transaction {
+            client: self.client,
+            savepoint: Some(name),
+            depth,
+            done: false,
+        })
+    }
+
+    pub async fn commit(self) -> Result<(), Status> {
+        let statement = match &self.savepoint {
+            Some(name) => format!("RELEASE SAVEPOINT {}", name),
+            None => "COMMIT".to_owned(),
+        };
+        self.end(&statement).await
+    }
+
+    pub async fn rollback(self) -> Result<(), Status> {
+        let statement = match &self.savepoint {
+            Some(name) => format!("ROLLBACK TO SAVEPOINT {}", name),
+            None => "ROLLBACK".to_owned(),
+        };
+        self.end(&statement).await
+    }
+
+    /// 按操作结果提交或回滚，回滚失败时仍返回操作本身的错误
+    pub async fn finish<T>(self, result: Result<T, Status>) -> Result<T, Status> {
+        match result {
+            Ok(value) => {
+                self.commit().await?;
+                Ok(value)
+            }
+            Err(s) => {
+                let _ = self.rollback().await;
+                Err(s)
+            }
+        }
+    }
+
+    async fn end(mut self, statement: &str) -> Result<(), Status> {
+        let result = self.client.batch_execute(statement).await;
+        self.done = true;
+        result.map_err(|e| Status::from_error(Box::new(e)))
+    }
+}
+
+impl std::ops::Deref for Transaction<'_> {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            // 与 tokio_postgres::Transaction 的做法相同：同步排入 ROLLBACK，由连接任务随后发送
+            self.client
+                .__private_api_rollback(self.savepoint.as_deref());
+        }
+    }
+}
+
+/// 在同一连接上以 BEGIN/COMMIT 包裹一组操作，任一步失败时回滚
+///
+/// `operation` 拿到事务本身，其中的语句都应通过它执行；需要再调用会开启事务的函数时，
+/// 改用对应的 `*_tx` 版本传入该事务，或用 [`Transaction::savepoint`] 显式嵌套。
+pub async fn in_transaction<T>(
+    client: &Client,
+    operation: impl AsyncFnOnce(&Transaction<'_>) -> Result<T, Status>,
+) -> Result<T, Status> {
+    let transaction = Transaction::begin(client).await?;
+    let result = operation(&transaction).await;
+    transaction.finish(result).await
+}
+
+/// 在当前事务中获取整个图的写锁，事务结束时释放
+///
+/// 用于“先校验、再写入”的变更，避免并发的变更都通过校验后写出重复的边或环。
+pub async fn lock_graph(client: &Client) -> Result<(), Status> {
+    client
+        .execute("SELECT pg_advisory_xact_lock(hashtext($1))", &[&GRAPH_NAME])
+        .await
+        .map_err(|e| Status::from_error(Box::new(e)))?;
+    Ok(())
+}
+
+/// 执行参数化 cypher，返回 AGE 结果行（单列 agtype）
+pub async fn execute_query(client: &Client, query: CypherQuery) -> Result<Vec<Row>, Status> {
+    let (cypher, params) = query.build()?;
+    let rows = match params {
+        Some(params) => client.query_cypher(GRAPH_NAME, &cypher, Some(params)).await,
+        None => client.query_cypher::<()>(GRAPH_NAME, &cypher, None).await,
+    };
+    rows.map_err(|e| Status::from_error(Box::new(e)))
+}
+
+pub async fn create_node(client: &Client, node: NodeTypeObject) -> Option<Status> {
+    let (node_type, name, properties) = match node {
+        NodeTypeObject::User(user) => (NodeType::User, user.name, user.properties),
+        NodeTypeObject::UserAttribute(user_attribute) => (
+            NodeType::UserAttribute,
+            user_attribute.name,
+            user_attribute.properties,
+        ),
+        NodeTypeObject::Object(object) => (NodeType::Object, object.name, object.properties),
+        NodeTypeObject::ObjectAttribute(object_attribute) => (
+            NodeType::ObjectAttribute,
+            object_attribute.name,
+            object_attribute.properties,
+        ),
+        NodeTypeObject::PolicyClass(policy_class) => (
+            NodeType::PolicyClass,
+            policy_class.name,
+            policy_class.properties,
+        ),
+    };
+    if let Err(e) = client
+        .unique_index(GRAPH_NAME, &node_type.fmt_full(), "unique_name", "name")
+        .await
+    {
+        return Some(Status::from_error(Box::new(e)));
+    }
+
+    // name 单独存储，避免 properties 中的同名键覆盖
+    let mut node_pattern = NodePattern::of(&node_type).property("name", name);
+    for (k, v) in properties.iter() {
+        if k.as_str() != "name" {
+            node_pattern = node_pattern.property(k, v.to_string());
+        }
+    }
+    let query = CypherQuery::new()
+        .create([Pattern::node(node_pattern)])
+        .return_(&format!("id({})", node_type));
+
+    let created = async {
+        let ids: Vec<i64> = rows_to(&execute_query(client, query).await?)?;
+        notify_changes(
+            client,
+            ids.into_iter()
+                .map(|id| GraphChange::node(ChangeKind::Created, id, node_type)),
+        )
+        .await
+    };
+    created.await.err()
+}
+
+/// 起点与终点使用不同的别名，同类型节点之间（UA→UA、OA→OA）的关系也能正确匹配
+fn create_edge_cypher(
+    origin_node_type: NodeType,
+    origin_node_id: i64,
+    target_node_type: NodeType,
+    target_node_id: i64,
+    edge: EdgePattern,
+) -> CypherQuery {
+    CypherQuery::new()
+        .match_([
+            Pattern::node(NodePattern::of(&origin_node_type).alias("origin")),
+            Pattern::node(NodePattern::of(&target_node_type).alias("target")),
+        ])
+        .where_([
+            Condition::id("origin", origin_node_id),
+            Condition::id("target", target_node_id),
+        ])
+        .create([Pattern::node(NodePattern::new("origin"))
+            .to(edge, NodePattern::new("target"))])
+        .return_("r")
+}
+
+pub fn create_assignment_cypher(
+    origin_node_type: NodeType,
+    origin_node_id: i64,
+    target_node_type: NodeType,
+    target_node_id: i64,
+    properties: AHashMap<FastStr, FastStr>,
+) -> CypherQuery {
+    let mut edge = EdgePattern::new().alias("r").label(ASSIGNMENT);
+    for (k, v) in properties.iter() {
+        edge = edge.property(k, v.to_string());
+    }
+    create_edge_cypher(
+        origin_node_type,
+        origin_node_id,
+        target_node_type,
+        target_node_id,
+        edge,
+    )
+}
+
+/// UA→OA 关联，访问权限集以数组存储在边的 `operations` 属性中
+pub fn create_operations_association_cypher(
+    user_attribute_id: i64,
+    object_attribute_id: i64,
+    operations: &AccessRightSet,
+) -> CypherQuery {
+    let edge = EdgePattern::new()
+        .alias("r")
+        .label(ASSOCIATION)
+        .property(OPERATIONS, operations.to_value());
+    create_edge_cypher(
+        NodeType::UserAttribute,
+        user_attribute_id,
+        NodeType::ObjectAttribute,
+        object_attribute_id,
+        edge,
+    )
+}
+
+/// 写入指派或关联边，校验和写入在同一事务中并持有图锁，并发写入不会绕过校验
+pub async fn assignment(client: &Client, assignment_combination: Assignment) -> Option<Status> {
+    in_transaction(client, async |tx| {
+        assignment_tx(tx, assignment_combination).await
+    })
+    .await
+    .err()
+}
+
+/// 在调用方的事务中写入指派或关联边，见 [`assignment`]
+pub async fn assignment_tx(
+    tx: &Transaction<'_>,
+    assignment_combination: Assignment,
+) -> Result<(), Status> {
+    let client: &Client = tx;
+    lock_graph(client).await?;
+    let (origin, target) = assignment_combination.endpoints();
+    validate_edge(client, origin, target).await?;
+    let query = match assignment_combination {
+        Assignment::U2UA((user_id, user_attribute_id)) => create_assignment_cypher(
+            NodeType::User,
+            user_id,
+            NodeType::UserAttribute,
+            user_attribute_id,
+            AHashMap::new(),
+        ),
+        Assignment::UA2UA((sub_user_attribute_id, user_attribute_id)) => create_assignment_cypher(
+            NodeType::UserAttribute,
+            sub_user_attribute_id,
+            NodeType::UserAttribute,
+            user_attribute_id,
+            AHashMap::new(),
+        ),
+        Assignment::UA2OA((user_attribute_id, object_attribute_id), operations) => {
+            if operations.is_empty() {
+                return Err(Status::invalid_argument(
+                    "association operations cannot be empty!",
+                ));
+            }
+            let resource_types: Vec<String> = search_resource_types(client, &[object_attribute_id])
+                .await?
+                .into_values()
+                .collect();
+            operations.validate_for(&resource_types)?;
+            create_operations_association_cypher(
+                user_attribute_id,
+                object_attribute_id,
+                &operations,
+            )
+        }
+        Assignment::UA2PC((user_attribute_id, policy_class_id)) => create_assignment_cypher(
+            NodeType::UserAttribute,
+            user_attribute_id,
+            NodeType::PolicyClass,
+            policy_class_id,
+            AHashMap::new(),
+        ),
+        Assignment::O2OA((object_id, object_attribute_id)) => create_assignment_cypher(
+            NodeType::Object,
+            object_id,
+            NodeType::ObjectAttribute,
+            object_attribute_id,
+            AHashMap::new(),
+        ),
+        Assignment::OA2OA((sub_object_attribute_id, object_attribute_id)) => {
+            create_assignment_cypher(
+                NodeType::ObjectAttribute,
+                sub_object_attribute_id,
+                NodeType::ObjectAttribute,
+                object_attribute_id,
+                AHashMap::new(),
+            )
+        }
+        Assignment::OA2PC((object_attribute_id, policy_class_id)) => create_assignment_cypher(
+            NodeType::ObjectAttribute,
+            object_attribute_id,
+            NodeType::PolicyClass,
+            policy_class_id,
+            AHashMap::new(),
+        ),
+    };
+
+    execute_query(client, query).await?;
+    let change = EdgeType::between(origin.0, target.0)
+        .map(|edge_type| GraphChange::edge(ChangeKind::Created, edge_type, origin.1, target.1));
+    notify_changes(client, change).await
+}
+
+/// 写入边之前的校验：两端节点存在且类型与声明一致，边尚不存在，指派边不形成环
+pub async fn validate_edge(
+    client: &Client,
+    origin: (NodeType, i64),
+    target: (NodeType, i64),
+) -> Result<(), Status> {
+    let (origin_node_type, origin_id) = origin;
+    let (target_node_type, target_id) = target;
+    let node_types = search_node_types(client, &[origin_id, target_id]).await?;
+    for (node_type, id) in [origin, target] {
+        match node_types.get(&id) {
+            Some(actual) if *actual == node_type => {}
+            Some(actual) => {
+                return Err(Status::failed_precondition(format!(
+                    "node: {} is a {:?}, expected {:?}!",
+                    id, actual, node_type
+                )))
+            }
+            None => {
+                return Err(Status::failed_precondition(format!(
+                    "node: {} not found!",
+                    id
+                )))
+            }
+        }
+    }
+
+    if !search_edges(client, origin_id, target_id).await?.is_empty() {
+        return Err(Status::already_exists(format!(
+            "edge: {} -> {} exists!",
+            origin_id, target_id
+        )));
+    }
+
+    // 只有 UA→UA、OA→OA 可能成环：目标已（间接）指派到起点时拒绝
+    if origin_node_type.can_assign_to(&target_node_type) {
+        if origin_id == target_id {
+            return Err(Status::failed_precondition(
+                "node cannot be assigned to itself!",
+            ));
+        }
+        let graph = PolicySubgraph::load(client, &[target_id]).await?;
+        if graph.assigned_closure(target_id).contains(&origin_id) {
+            return Err(Status::failed_precondition(format!(
+                "assignment: {} -> {} would create a cycle!",
+                origin_id, target_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct NodeLabel {
+    id: i64,
+    label: String,
+}
+
+/// 查询一批节点的类型，不存在的节点不出现在结果中
+pub async fn search_node_types(
+    client: &Client,
+    ids: &[i64],
+) -> Result<HashMap<i64, NodeType>, Status> {
+    let query = CypherQuery::new()
+        .match_([Pattern::node(NodePattern::new("n"))])
+        .where_([Condition::id_in("n", ids.to_vec())])
+        .return_("{id: id(n), label: label(n)}");
+    let rows = execute_query(client, query).await?;
+    Ok(rows_to::<NodeLabel>(&rows)?
+        .into_iter()
+        .filter_map(|node| NodeType::from_label(&node.label).map(|t| (node.id, t)))
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct NodeResourceType {
+    id: i64,
+    resource_type: Option<String>,
+}
+
+/// 查询一批节点声明的资源类型，未声明或不存在的节点不出现在结果中
+pub async fn search_resource_types(
+    client: &Client,
+    ids: &[i64],
+) -> Result<HashMap<i64, String>, Status> {
+    let query = CypherQuery::new()
+        .match_([Pattern::node(NodePattern::new("n"))])
+        .where_([Condition::id_in("n", ids.to_vec())])
+        .return_(&format!("{{id: id(n), resource_type: n.{}}}", RESOURCE_TYPE));
+    let rows = execute_query(client, query).await?;
+    Ok(rows_to::<NodeResourceType>(&rows)?
+        .into_iter()
+        .filter_map(|node| node.resource_type.map(|t| (node.id, t)))
+        .collect())
+}
+
+/// 只匹配指派/关联边，禁止关系由 [`prohibition`] 单独维护
+fn edge_between_cypher(origin_id: i64, target_id: i64) -> CypherQuery {
+    CypherQuery::new()
+        .match_([Pattern::node(NodePattern::new("origin"))
+            .to(EdgePattern::new().alias("r"), NodePattern::new("target"))])
+        .where_([
+            Condition::id("origin", origin_id),
+            Condition::id("target", target_id),
+            Condition::label_in("r", &[ASSIGNMENT, ASSOCIATION]),
+        ])
+}
+
+/// 查询 origin→target 之间的边
+pub async fn search_edges(
+    client: &Client,
+    origin_id: i64,
+    target_id: i64,
+) -> Result<Vec<EdgeRecord>, Status> {
+    let query = edge_between_cypher(origin_id, target_id).return_(EDGE_RECORD);
+    let rows = execute_query(client, query).await?;
+    rows_to(&rows)
+}
+
+/// 删除 origin→target 之间的边，不存在时返回 NotFound
+pub async fn delete_edge(client: &Client, origin_id: i64, target_id: i64) -> Result<(), Status> {
+    let edges = search_edges(client, origin_id, target_id).await?;
+    if edges.is_empty() {
+        return Err(Status::not_found("edge not found!"));
+    }
+    let query = edge_between_cypher(origin_id, target_id).delete(&["r"], false);
+    execute_query(client, query).await?;
+    notify_changes(
+        client,
+        edges
+            .iter()
+            .filter_map(|edge| GraphChange::from_edge(ChangeKind::Deleted, edge)),
+    )
+    .await
+}
+
+/// 删除指派（或关联）边，边两端的类型需与声明一致
+pub async fn delete_assignment(
+    client: &Client,
+    assignment_combination: Assignment,
+) -> Result<(), Status> {
+    let ((origin_node_type, origin_id), (target_node_type, target_id)) =
+        assignment_combination.endpoints();
+    let edge = search_edges(client, origin_id, target_id)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Status::not_found("edge not found!"))?;
+    if edge.start_type() != Some(origin_node_type) || edge.end_type() != Some(target_node_type) {
+        return Err(Status::failed_precondition(format!(
+            "edge: {} -> {} is not a {:?} -> {:?} relation!",
+            origin_id, target_id, origin_node_type, target_node_type
+        )));
+    }
+    delete_edge(client, origin_id, target_id).await
+}
+
+/// 把 origin→old_target 的边改指向 new_target，保留边的标签和属性，整体在一个事务中完成
+pub async fn retarget_edge(
+    client: &Client,
+    origin_id: i64,
+    old_target_id: i64,
+    new_target_id: i64,
+) -> Result<(), Status> {
+    in_transaction(client, async |tx| {
+        lock_graph(tx).await?;
+        let edge = search_edges(tx, origin_id, old_target_id)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Status::not_found("edge not found!"))?;
+        let origin_node_type = edge
+            .start_type()
+            .ok_or_else(|| Status::aborted("node type error!"))?;
+        let new_target_node_type = *search_node_types(tx, &[new_target_id])
+            .await?
+            .get(&new_target_id)
+            .ok_or_else(|| Status::not_found("node not found!"))?;
+
+        // 指派只能改指向另一个可指派的节点，关联同理
+        let legal = if edge.is_association() {
+            origin_node_type.can_associate_with(&new_target_node_type)
+        } else {
+            origin_node_type.can_assign_to(&new_target_node_type)
+        };
+        if !legal {
+            return Err(Status::failed_precondition(format!(
+                "illegal NGAC relation: {:?} -> {:?}",
+                origin_node_type, new_target_node_type
+            )));
+        }
+        validate_edge(
+            tx,
+            (origin_node_type, origin_id),
+            (new_target_node_type, new_target_id),
+        )
+        .await?;
+        // 关联的操作要对新 OA 下的资源类型仍然有效
+        if edge.is_association() {
+            let resource_types: Vec<String> = search_resource_types(tx, &[new_target_id])
+                .await?
+                .into_values()
+                .collect();
+            edge.operations().validate_for(&resource_types)?;
+        }
+
+        let mut new_edge = EdgePattern::new().alias("r").label(&edge.label);
+        if let Some(properties) = edge.properties.as_object() {
+            for (k, v) in properties {
+                new_edge = new_edge.property(k, v.clone());
+            }
+        }
+        let create = create_edge_cypher(
+            origin_node_type,
+            origin_id,
+            new_target_node_type,
+            new_target_id,
+            new_edge,
+        );
+        execute_query(tx, create).await?;
+        let delete = edge_between_cypher(origin_id, old_target_id).delete(&["r"], false);
+        execute_query(tx, delete).await?;
+
+        let changes = edge.edge_type().map(|edge_type| {
+            [
+                GraphChange::edge(ChangeKind::Created, edge_type, origin_id, new_target_id),
+                GraphChange::edge(ChangeKind::Deleted, edge_type, origin_id, old_target_id),
+            ]
+        });
+        notify_changes(tx, changes.into_iter().flatten()).await
+    })
+    .await
+}
+
+pub fn search_node_cypher(
+    node_type: NodeType,
+    name: Option<&str>,
+    id: Option<i64>,
+    properties: AHashMap<FastStr, FastStr>,
+) -> Result<CypherQuery, Status> {
+    if name.is_none() && id.is_none() {
+        return Err(Status::invalid_argument(
+            "The name and ID cannot both be empty!",
+        ));
+    }
+    let mut node = NodePattern::of(&node_type);
+    if let Some(name) = name {
+        node = node.property("name", name);
+    }
+    let mut conditions = Condition::properties(node_type, &properties);
+    if let Some(id) = id {
+        conditions.insert(0, Condition::id(node_type, id));
+    }
+    Ok(CypherQuery::new()
+        .match_([Pattern::node(node)])
+        .where_(conditions)
+        .return_(&node_type.to_string()))
+}
+
+/// 只沿指派边查找 origin 指派到的目标节点
+pub fn search_origin_id_to_assigned_target_node_path_cypher(
+    origin_node_type: NodeType,
+    origin_id: i64,
+    target_node_type: NodeType,
+    target_node_name: Option<&str>,
+    target_node_id: Option<i64>,
+    target_node_properties: AHashMap<FastStr, FastStr>,
+    adjacent: bool,
+) -> Result<CypherQuery, Status> {
+    search_origin_id_to_target_node_path_cypher(
+        (origin_node_type, origin_id),
+        target_node_type,
+        target_node_name,
+        target_node_id,
+        target_node_properties,
+        Some(EdgeType::Assignment),
+        adjacent,
+    )
+}
+
+/// `edge_type` 限定沿途边的类型，为 None 时任意类型的边都可经过
+pub fn search_origin_id_to_target_node_path_cypher(
+    origin: (NodeType, i64),
+    target_node_type: NodeType,
+    target_node_name: Option<&str>,
+    target_node_id: Option<i64>,
+    target_node_properties: AHashMap<FastStr, FastStr>,
+    edge_type: Option<EdgeType>,
+    adjacent: bool,
+) -> Result<CypherQuery, Status> {
+    let (origin_node_type, origin_id) = origin;
+    if target_node_name.is_none() && target_node_id.is_none() {
+        return Err(Status::invalid_argument(
+            "The name and ID cannot both be empty!",
+        ));
+    }
+    let mut target = NodePattern::of(&target_node_type)
+        .alias("target")
+        .properties(&target_node_properties);
+    if let Some(name) = target_node_name {
+        target = target.property("name", name);
+    }
+    let mut conditions = vec![Condition::id("origin", origin_id)];
+    if let Some(id) = target_node_id {
+        conditions.push(Condition::id("target", id));
+    }
+    Ok(CypherQuery::new()
+        .match_([origin_to_target_path(
+            NodePattern::of(&origin_node_type).alias("origin"),
+            target,
+            edge_type,
+            adjacent,
+        )])
+        .where_(conditions)
+        .return_("target"))
+}
+
+/// `(origin)-[edge_type*]->(target)`，adjacent 为 true 时只匹配一跳
+fn origin_to_target_path(
+    origin: NodePattern,
+    target: NodePattern,
+    edge_type: Option<EdgeType>,
+    adjacent: bool,
+) -> Pattern {
+    let mut edge = EdgePattern::new();
+    if let Some(edge_type) = edge_type {
+        edge = edge.label(edge_type.label());
+    }
+    if !adjacent {
+        edge = edge.any_length();
+    }
+    Pattern::node(origin).to(edge, target)
+}
+
+/// [`search_origin_id_to_assigned_target_node_path_cypher`] 的反向查询：
+/// 沿指派边直接或间接指派到 target_ids 中任一节点的 origin_node_type 类型节点，返回去重后的 id
+pub fn search_target_ids_to_assigned_origin_node_path_cypher(
+    origin_node_type: NodeType,
+    target_ids: Vec<i64>,
+) -> CypherQuery {
+    CypherQuery::new()
+        .match_([origin_to_target_path(
+            NodePattern::of(&origin_node_type).alias("origin"),
+            NodePattern::new("target"),
+            Some(EdgeType::Assignment),
+            false,
+        )])
+        .where_([Condition::id_in("target", target_ids)])
+        .return_("DISTINCT id(origin)")
+}
+
+pub async fn search_node(
+    client: &Client,
+    node_type: NodeType,
+    name: Option<&str>,
+    id: Option<i64>,
+    properties: AHashMap<FastStr, FastStr>,
+) -> Result<VertexTypeObject, Status> {
+    let query = search_node_cypher(node_type, name, id, properties)?;
+
+    let rows = execute_query(client, query).await?;
+    if rows.is_empty() {
+        return Err(Status::not_found("node not found!"));
+    }
+    match node_type {
+        NodeType::User => {
+            let user: Vertex<User> = rows[0].get(0);
+            Ok(VertexTypeObject::User(user))
+        }
+        NodeType::UserAttribute => {
+            let user_attribute: Vertex<UserAttribute> = rows[0].get(0);
+            Ok(VertexTypeObject::UserAttribute(user_attribute))
+        }
+        NodeType::Object => {
+            let object: Vertex<Object> = rows[0].get(0);
+            Ok(VertexTypeObject::Object(object))
+        }
+        NodeType::ObjectAttribute => {
+            let object_attribute: Vertex<ObjectAttribute> = rows[0].get(0);
+            Ok(VertexTypeObject::ObjectAttribute(object_attribute))
+        }
+        NodeType::PolicyClass => {
+            let policy_calass: Vertex<PolicyClass> = rows[0].get(0);
+            Ok(VertexTypeObject::PolicyClass(policy_calass))
+        }
+    }
+}
+
+pub async fn search_user_attribute_node(
+    client: &Client,
+    id: Option<i64>,
+    attribute_name: Option<&str>,
+    properties: AHashMap<FastStr, FastStr>,
+) -> Result<Vec<Row>, Status> {
+    let query = search_node_cypher(NodeType::UserAttribute, attribute_name, id, properties)?;
+    let user_attribute = execute_query(client, query).await?;
+    if user_attribute.is_empty() {
+        return Err(Status::not_found("User attribute not found!"));
+    }
+    Ok(user_attribute)
+}
+
+pub async fn search_nodes(
+    client: &Client,
+    node_type: NodeType,
+    id: Option<i64>,
+    name: Option<&str>,
+    properties: AHashMap<FastStr, FastStr>,
+) -> Result<Vec<Row>, Status> {
+    let query = search_node_cypher(node_type, name, id, properties)?;
+    let nodes = execute_query(client, query).await?;
+    if nodes.is_empty() {
+        return Err(Status::not_found(format!("{} not found!", node_type.fmt_full())));
+    }
+    Ok(nodes)
+}
+
+/// 按属性列出某类型的全部节点，不要求 name 或 id
+pub async fn list_nodes(
+    client: &Client,
+    node_type: NodeType,
+    properties: AHashMap<FastStr, FastStr>,
+) -> Result<Vec<Row>, Status> {
+    let query = CypherQuery::new()
+        .match_([Pattern::node(NodePattern::of(&node_type))])
+        .where_(Condition::properties(node_type, &properties))
+        .return_(&node_type.to_string());
+    execute_query(client, query).await
+}
+
+/// 确保 ngac 图存在，不存在时创建
+pub async fn ensure_graph(client: &Client) -> Result<(), Status> {
+    let rows = client
+        .query(
+            "SELECT 1 FROM ag_catalog.ag_graph WHERE name = $1",
+            &[&GRAPH_NAME],
+        )
+        .await
+        .map_err(|e| Status::from_error(Box::new(e)))?;
+    if rows.is_empty() {
+        client
+            .create_graph(GRAPH_NAME)
+            .await
+            .map_err(|e| Status::from_error(Box::new(e)))?;
+    }
+    Ok(())
+}
+
+/// 确保指定名称的策略类存在，返回其 id
+pub async fn ensure_policy_class(client: &Client, name: &str) -> Result<i64, Status> {
+    match search_node(client, NodeType::PolicyClass, Some(name), None, AHashMap::new()).await {
+        Ok(VertexTypeObject::PolicyClass(pc)) => return Ok(pc.id() as i64),
+        Ok(_) => return Err(Status::aborted("node type error!")),
+        Err(e) => {
+            if e.code() != Code::NotFound {
+                return Err(e);
+            }
+        }
+    }
+
+    let policy_class = PolicyClass {
+        name: name.to_owned(),
+        properties: AHashMap::new(),
+    };
+    if let Some(s) = create_node(client, NodeTypeObject::PolicyClass(policy_class)).await {
+        return Err(s);
+    }
+    match search_node(client, NodeType::PolicyClass, Some(name), None, AHashMap::new()).await? {
+        VertexTypeObject::PolicyClass(pc) => Ok(pc.id() as i64),
+        _ => Err(Status::aborted("node type error!")),
+    }
+}
+
+/// 修改节点的 name 和属性，未出现的属性保持不变
+pub async fn update_node(
+    client: &Client,
+    node_type: NodeType,
+    id: i64,
+    name: Option<&str>,
+    properties: AHashMap<FastStr, FastStr>,
+) -> Result<(), Status> {
+    search_node(client, node_type, None, Some(id), AHashMap::new()).await?;
+
+    let mut assignments: Vec<(String, serde_json::Value)> = properties
+        .iter()
+        .filter(|(k, _)| k.as_str() != "name")
+        .map(|(k, v)| (k.to_string(), v.to_string().into()))
+        .collect();
+    if let Some(name) = name {
+        assignments.push(("name".to_owned(), name.into()));
+    }
+    if assignments.is_empty() {
+        return Ok(());
+    }
+    let alias = node_type.to_string();
+    let query = CypherQuery::new()
+        .match_([Pattern::node(NodePattern::of(&node_type))])
+        .where_([Condition::id(node_type, id)])
+        .set(&alias, assignments);
+    execute_query(client, query).await?;
+    notify_changes(client, [GraphChange::node(ChangeKind::Updated, id, node_type)]).await
+}
+
+/// 删除节点时对子节点（指派到该节点的节点）的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeletePolicy {
+    /// 仍有子节点时拒绝删除
+    #[default]
+    Refuse,
+    /// 删除节点及其所有边，子节点保留
+    Cascade,
+}
+
+/// 删除节点及其所有边
+///
+/// 禁止关系不会随节点静默删除，否则会扩大访问范围：节点被禁止关系引用时拒绝删除，
+/// 只有以该用户为主体的禁止关系随用户一起删除（主体已不存在）并发出通知。
+pub async fn delete_node(
+    client: &Client,
+    node_type: NodeType,
+    id: i64,
+    policy: DeletePolicy,
+) -> Result<(), Status> {
+    in_transaction(client, async |tx| {
+        delete_node_tx(tx, node_type, id, policy).await
+    })
+    .await
+}
+
+/// 在调用方的事务中删除节点，见 [`delete_node`]
+pub async fn delete_node_tx(
+    tx: &Transaction<'_>,
+    node_type: NodeType,
+    id: i64,
+    policy: DeletePolicy,
+) -> Result<(), Status> {
+    let client: &Client = tx;
+    // 持有图锁，检查子节点与禁止关系之后不会有并发写入新的引用
+    lock_graph(client).await?;
+    search_node(client, node_type, None, Some(id), AHashMap::new()).await?;
+    if policy == DeletePolicy::Refuse
+        && !fetch_in_edges(client, &[id], &[EdgeType::Assignment])
+            .await?
+            .is_empty()
+    {
+        return Err(Status::failed_precondition(format!(
+            "node: {} still has assigned children!",
+            id
+        )));
+    }
+
+    // 边随节点一起删除，先记下以便通知
+    let mut edges = fetch_out_edges(client, &[id], &[]).await?;
+    edges.extend(fetch_in_edges(client, &[id], &[]).await?);
+
+    let prohibitions = prohibition::Prohibition::from_edges(edges.iter());
+    if let Some(referenced) = prohibitions
+        .iter()
+        .find(|p| node_type != NodeType::User || p.subject_id != id)
+    {
+        return Err(Status::failed_precondition(format!(
+            "node: {} is referenced by prohibition: {}!",
+            id, referenced.name
+        )));
+    }
+    for p in prohibitions.iter() {
+        prohibition::delete_prohibition_tx(tx, &p.name).await?;
+    }
+
+    let alias = node_type.to_string();
+    let query = CypherQuery::new()
+        .match_([Pattern::node(NodePattern::of(&node_type))])
+        .where_([Condition::id(node_type, id)])
+        .delete(&[&alias], true);
+    execute_query(client, query).await?;
+    let changes = edges
+        .iter()
+        .filter(|edge| !edge.is_prohibition())
+        .filter_map(|edge| GraphChange::from_edge(ChangeKind::Deleted, edge))
+        .chain([GraphChange::node(ChangeKind::Deleted, id, node_type)]);
+    notify_changes(client, changes).await
+}
+
+// pub async fn search_user_attribute_node_with_assigned_id(
+//     assigned_id: i64,
+//     assigned_node_type: UserAttributeOriginNodeType,
+//     attribute_name: Option<&str>,
+//     id: Option<i64>,
+//     properties: AHashMap<FastStr, FastStr>,
+//     adjacent: bool,
+// ) -> Result<Vertex<UserAttribute>, Status> {
+//     let age = match get_age().await {
+//         Ok(age) => age,
+//         Err(e) => return Err(Status::from_error(e)),
+//     };
+//     let mut target_node_type = NodeType::UserAttribute;
+//     if assigned_node_type == UserAttributeOriginNodeType::USER {
+//         target_node_type = NodeType::User;
+//     }
+//     let cypher = match search_origin_id_to_assigned_target_node_path_cypher(
+//         target_node_type,
+//         assigned_id,
+//         NodeType::UserAttribute,
+//         attribute_name,
+//         id,
+//         properties,
+//         adjacent,
+//     ) {
+//         Ok(cypher) => cypher,
+//         Err(s) => return Err(s),
+//     };
+
+//     let user_attribute = match age.query_cypher::<()>(GRAPH_NAME, &cypher, None).await {
+//         Ok(rows) => {
+//             if !rows.is_empty() {
+//                 let node: Vertex<UserAttribute> = rows[0].get(0);
+//                 node
+//             } else {
+//                 return Err(Status::not_found("User attribute not found!"));
+//             }
+//         }
+//         Err(e) => return Err(Status::from_error(Box::new(e))),
+//     };
+//     Ok(user_attribute)
+// }
\ No newline at end of file
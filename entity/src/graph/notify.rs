@@ -0,0 +1,118 @@
+use apache_age::tokio::Client;
+use serde::{Deserialize, Serialize};
+use volo_grpc::Status;
+
+use pool::age::{notify, GRAPH_CHANNEL};
+
+use super::decision::EdgeRecord;
+use super::edge::EdgeType;
+use super::NodeType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// 一次节点或边的变更，序列化为 JSON 后作为 [`GRAPH_CHANNEL`] 通知的 payload
+///
+/// 只携带标识，订阅方需要详情时自行查询，如
+/// `{"target":"edge","kind":"created","edge_type":"Assignment","start_id":1,"end_id":2}`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "target", rename_all = "snake_case")]
+pub enum GraphChange {
+    Node {
+        kind: ChangeKind,
+        id: i64,
+        node_type: NodeType,
+    },
+    Edge {
+        kind: ChangeKind,
+        edge_type: EdgeType,
+        start_id: i64,
+        end_id: i64,
+    },
+}
+
+impl GraphChange {
+    pub fn node(kind: ChangeKind, id: i64, node_type: NodeType) -> Self {
+        GraphChange::Node {
+            kind,
+            id,
+            node_type,
+        }
+    }
+
+    pub fn edge(kind: ChangeKind, edge_type: EdgeType, start_id: i64, end_id: i64) -> Self {
+        GraphChange::Edge {
+            kind,
+            edge_type,
+            start_id,
+            end_id,
+        }
+    }
+
+    /// 标签不是已知边类型的边返回 None
+    pub fn from_edge(kind: ChangeKind, edge: &EdgeRecord) -> Option<Self> {
+        let edge_type = edge.edge_type()?;
+        Some(GraphChange::edge(
+            kind,
+            edge_type,
+            edge.start_id,
+            edge.end_id,
+        ))
+    }
+
+    /// 变更涉及的节点：节点本身或边的两端
+    pub fn ids(&self) -> Vec<i64> {
+        match self {
+            GraphChange::Node { id, .. } => vec![*id],
+            GraphChange::Edge {
+                start_id, end_id, ..
+            } => vec![*start_id, *end_id],
+        }
+    }
+
+    pub fn parse(payload: &str) -> Result<Self, Status> {
+        serde_json::from_str(payload).map_err(|e| Status::invalid_argument(e.to_string()))
+    }
+}
+
+/// 在执行变更的连接上发送通知，变更处于事务中时随事务提交送达
+pub async fn notify_changes(
+    client: &Client,
+    changes: impl IntoIterator<Item = GraphChange>,
+) -> Result<(), Status> {
+    for change in changes {
+        let payload =
+            serde_json::to_string(&change).map_err(|e| Status::from_error(Box::new(e)))?;
+        notify(client, GRAPH_CHANNEL, &payload)
+            .await
+            .map_err(|e| Status::from_error(Box::new(e)))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangeKind, GraphChange};
+    use crate::graph::edge::EdgeType;
+    use crate::graph::NodeType;
+
+    #[test]
+    fn payload_round_trip() {
+        let changes = [
+            GraphChange::node(ChangeKind::Deleted, 7, NodeType::User),
+            GraphChange::edge(ChangeKind::Created, EdgeType::Assignment, 1, 2),
+        ];
+        for change in changes.iter() {
+            let payload = serde_json::to_string(change).unwrap();
+            assert_eq!(&GraphChange::parse(&payload).unwrap(), change);
+        }
+        assert_eq!(changes[0].ids(), vec![7]);
+        assert_eq!(changes[1].ids(), vec![1, 2]);
+        assert!(GraphChange::parse("{\"target\":\"unknown\"}").is_err());
+    }
+}
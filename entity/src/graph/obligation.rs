@@ -0,0 +1,523 @@
+use apache_age::tokio::Client;
+use pilota::AHashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use volo_grpc::Status;
+
+use super::access_right::AccessRightSet;
+use super::decision::PolicySubgraph;
+use super::{
+    assignment_tx, create_node, in_transaction, search_node, search_node_types, Assignment,
+    NodeType, NodeTypeObject, Transaction,
+};
+
+/// person-center 在图变更成功后发出的事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GraphEvent {
+    NodeCreated {
+        actor_id: Option<i64>,
+        node_id: i64,
+        node_type: NodeType,
+        name: String,
+    },
+    Assigned {
+        actor_id: Option<i64>,
+        origin_id: i64,
+        origin_type: NodeType,
+        target_id: i64,
+        target_type: NodeType,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    NodeCreated,
+    Assigned,
+}
+
+impl GraphEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            GraphEvent::NodeCreated { .. } => EventKind::NodeCreated,
+            GraphEvent::Assigned { .. } => EventKind::Assigned,
+        }
+    }
+
+    pub fn actor_id(&self) -> Option<i64> {
+        match self {
+            GraphEvent::NodeCreated { actor_id, .. } | GraphEvent::Assigned { actor_id, .. } => {
+                *actor_id
+            }
+        }
+    }
+
+    /// 事件的主体节点：新建的节点或指派的起点
+    pub fn node(&self) -> (i64, NodeType) {
+        match self {
+            GraphEvent::NodeCreated {
+                node_id, node_type, ..
+            } => (*node_id, *node_type),
+            GraphEvent::Assigned {
+                origin_id,
+                origin_type,
+                ..
+            } => (*origin_id, *origin_type),
+        }
+    }
+}
+
+/// 事件匹配条件，未设置的条件不做限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPattern {
+    pub kind: EventKind,
+    /// 主体节点类型
+    #[serde(default)]
+    pub node_type: Option<NodeType>,
+    /// 执行者需（直接或间接）属于该名称的用户属性
+    #[serde(default)]
+    pub actor_in: Option<String>,
+    /// 主体节点需（直接或间接）属于该名称的属性
+    #[serde(default)]
+    pub node_in: Option<String>,
+}
+
+/// 响应中引用的节点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operand {
+    /// 事件主体节点
+    EventNode,
+    /// 事件执行者
+    Actor,
+    /// 按类型和名称查找的已有节点
+    Named { node_type: NodeType, name: String },
+    /// 同一义务中之前 `CreateNode` 绑定的节点
+    Bound(String),
+}
+
+/// 义务的响应动作，`name` 支持 `{node_name}`、`{node_id}`、`{actor_id}` 占位符
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ObligationResponse {
+    CreateNode {
+        node_type: NodeType,
+        name: String,
+        bind: String,
+    },
+    Assign {
+        child: Operand,
+        parent: Operand,
+    },
+    Associate {
+        user_attribute: Operand,
+        object_attribute: Operand,
+        operations: Vec<String>,
+    },
+}
+
+/// NGAC 义务：事件满足条件时依次执行响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Obligation {
+    pub name: String,
+    pub pattern: EventPattern,
+    pub responses: Vec<ObligationResponse>,
+}
+
+struct EventContext<'a> {
+    event: &'a GraphEvent,
+    node_name: String,
+    bindings: HashMap<String, i64>,
+}
+
+impl EventContext<'_> {
+    fn render(&self, template: &str) -> String {
+        template
+            .replace("{node_name}", &self.node_name)
+            .replace("{node_id}", &self.event.node().0.to_string())
+            .replace(
+                "{actor_id}",
+                &self
+                    .event
+                    .actor_id()
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+            )
+    }
+
+    async fn resolve(&self, client: &Client, operand: &Operand) -> Result<i64, Status> {
+        match operand {
+            Operand::EventNode => Ok(self.event.node().0),
+            Operand::Actor => self
+                .event
+                .actor_id()
+                .ok_or_else(|| Status::failed_precondition("event has no actor!")),
+            Operand::Named { node_type, name } => {
+                Ok(
+                    search_node(client, *node_type, Some(name), None, AHashMap::new())
+                        .await?
+                        .id(),
+                )
+            }
+            Operand::Bound(bind) => self
+                .bindings
+                .get(bind)
+                .copied()
+                .ok_or_else(|| Status::invalid_argument(format!("unbound operand: {}", bind))),
+        }
+    }
+}
+
+/// 查找属性节点名称对应的 id，用户属性和对象属性都可能
+async fn attribute_id(client: &Client, name: &str) -> Result<Option<i64>, Status> {
+    for node_type in [NodeType::UserAttribute, NodeType::ObjectAttribute] {
+        match search_node(client, node_type, Some(name), None, AHashMap::new()).await {
+            Ok(node) => return Ok(Some(node.id())),
+            Err(e) if e.code() == volo_grpc::Code::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(None)
+}
+
+impl Obligation {
+    /// 只比较事件本身携带的信息，不访问图
+    pub fn accepts(&self, event: &GraphEvent) -> bool {
+        self.pattern.kind == event.kind()
+            && self
+                .pattern
+                .node_type
+                .is_none_or(|node_type| node_type == event.node().1)
+            && (self.pattern.actor_in.is_none() || event.actor_id().is_some())
+    }
+
+    /// 结合图中的指派关系判断事件是否命中
+    pub async fn matches(
+        &self,
+        client: &Client,
+        graph: &PolicySubgraph,
+        event: &GraphEvent,
+    ) -> Result<bool, Status> {
+        if !self.accepts(event) {
+            return Ok(false);
+        }
+        let mut attributes = HashMap::new();
+        for name in [&self.pattern.actor_in, &self.pattern.node_in]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(id) = attribute_id(client, name).await? {
+                attributes.insert(name.clone(), id);
+            }
+        }
+        Ok(self.matches_resolved(graph, event, &attributes))
+    }
+
+    /// 条件中的属性名称已解析为 id（`attributes`，不存在的属性不在其中）后的匹配，不访问数据库
+    pub fn matches_resolved(
+        &self,
+        graph: &PolicySubgraph,
+        event: &GraphEvent,
+        attributes: &HashMap<String, i64>,
+    ) -> bool {
+        if !self.accepts(event) {
+            return false;
+        }
+        let within = |name: &String, node_id: i64| {
+            attributes
+                .get(name)
+                .is_some_and(|id| graph.assigned_closure(node_id).contains(id))
+        };
+        if let (Some(actor_in), Some(actor_id)) = (&self.pattern.actor_in, event.actor_id()) {
+            if !within(actor_in, actor_id) {
+                return false;
+            }
+        }
+        if let Some(node_in) = &self.pattern.node_in {
+            if !within(node_in, event.node().0) {
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn respond(&self, tx: &Transaction<'_>, event: &GraphEvent) -> Result<(), Status> {
+        let client: &Client = tx;
+        let (node_id, node_type) = event.node();
+        let node_name = search_node(client, node_type, None, Some(node_id), AHashMap::new())
+            .await?
+            .name()
+            .to_owned();
+        let mut context = EventContext {
+            event,
+            node_name,
+            bindings: HashMap::new(),
+        };
+
+        for response in self.responses.iter() {
+            match response {
+                ObligationResponse::CreateNode {
+                    node_type,
+                    name,
+                    bind,
+                } => {
+                    let name = context.render(name);
+                    let node = NodeTypeObject::new(*node_type, name.clone(), AHashMap::new());
+                    if let Some(s) = create_node(client, node).await {
+                        return Err(s);
+                    }
+                    let id = search_node(client, *node_type, Some(&name), None, AHashMap::new())
+                        .await?
+                        .id();
+                    context.bindings.insert(bind.clone(), id);
+                }
+                ObligationResponse::Assign { child, parent } => {
+                    let child = context.resolve(client, child).await?;
+                    let parent = context.resolve(client, parent).await?;
+                    let node_types = search_node_types(client, &[child, parent]).await?;
+                    let (Some(child_type), Some(parent_type)) =
+                        (node_types.get(&child), node_types.get(&parent))
+                    else {
+                        return Err(Status::not_found("node not found!"));
+                    };
+                    if !child_type.can_assign_to(parent_type) {
+                        return Err(Status::failed_precondition(format!(
+                            "illegal NGAC assignment: {:?} -> {:?}",
+                            child_type, parent_type
+                        )));
+                    }
+                    let assignment_combination = Assignment::from_node_types(
+                        *child_type,
+                        child,
+                        *parent_type,
+                        parent,
+                        AccessRightSet::new(),
+                    )
+                    .ok_or_else(|| Status::failed_precondition("illegal NGAC relation!"))?;
+                    assignment_tx(tx, assignment_combination).await?;
+                }
+                ObligationResponse::Associate {
+                    user_attribute,
+                    object_attribute,
+                    operations,
+                } => {
+                    let user_attribute = context.resolve(client, user_attribute).await?;
+                    let object_attribute = context.resolve(client, object_attribute).await?;
+                    let node_types =
+                        search_node_types(client, &[user_attribute, object_attribute]).await?;
+                    if node_types.get(&user_attribute) != Some(&NodeType::UserAttribute)
+                        || node_types.get(&object_attribute) != Some(&NodeType::ObjectAttribute)
+                    {
+                        return Err(Status::failed_precondition(
+                            "association must be from a user attribute to an object attribute!",
+                        ));
+                    }
+                    let assignment_combination = Assignment::UA2OA(
+                        (user_attribute, object_attribute),
+                        AccessRightSet::from(&operations[..]),
+                    );
+                    assignment_tx(tx, assignment_combination).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct ObligationEngine;
+
+static OBLIGATIONS: OnceLock<RwLock<Vec<Obligation>>> = OnceLock::new();
+
+impl ObligationEngine {
+    fn obligations() -> &'static RwLock<Vec<Obligation>> {
+        OBLIGATIONS.get_or_init(|| RwLock::new(Vec::new()))
+    }
+
+    /// 注册义务，同名的义务被替换
+    pub fn register(obligation: Obligation) {
+        let mut obligations = ObligationEngine::obligations()
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        obligations.retain(|o| o.name != obligation.name);
+        obligations.push(obligation);
+    }
+
+    pub fn unregister(name: &str) -> bool {
+        let mut obligations = ObligationEngine::obligations()
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        let len = obligations.len();
+        obligations.retain(|o| o.name != name);
+        obligations.len() != len
+    }
+
+    pub fn list() -> Vec<Obligation> {
+        ObligationEngine::obligations()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 从 JSON 数组加载义务，返回加载的数量
+    pub fn load_json(json: &str) -> Result<usize, Status> {
+        let obligations: Vec<Obligation> =
+            serde_json::from_str(json).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let count = obligations.len();
+        for obligation in obligations {
+            ObligationEngine::register(obligation);
+        }
+        Ok(count)
+    }
+
+    /// 对事件执行所有命中的义务
+    ///
+    /// 每个义务的响应在一个事务中执行，一个义务失败时回滚它自己的响应，其余义务照常执行；
+    /// 响应产生的图变更不会再次触发义务。只有加载判定所需的子图失败时返回错误。
+    pub async fn evaluate(client: &Client, event: &GraphEvent) -> Result<Evaluation, Status> {
+        let candidates: Vec<Obligation> = ObligationEngine::list()
+            .into_iter()
+            .filter(|o| o.accepts(event))
+            .collect();
+        let mut evaluation = Evaluation::default();
+        if candidates.is_empty() {
+            return Ok(evaluation);
+        }
+
+        let mut starts = vec![event.node().0];
+        starts.extend(event.actor_id());
+        let graph = PolicySubgraph::load(client, &starts).await?;
+
+        for obligation in candidates.iter() {
+            let applied = async {
+                if !obligation.matches(client, &graph, event).await? {
+                    return Ok(false);
+                }
+                in_transaction(client, async |tx| obligation.respond(tx, event).await).await?;
+                Ok::<_, Status>(true)
+            }
+            .await;
+            match applied {
+                Ok(true) => evaluation.applied.push(obligation.name.clone()),
+                Ok(false) => {}
+                Err(s) => evaluation.failed.push((obligation.name.clone(), s)),
+            }
+        }
+        Ok(evaluation)
+    }
+}
+
+/// 一次事件的义务执行结果
+#[derive(Debug, Default)]
+pub struct Evaluation {
+    /// 命中并执行成功的义务
+    pub applied: Vec<String>,
+    /// 执行失败（已回滚）的义务及其错误
+    pub failed: Vec<(String, Status)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{EventContext, GraphEvent, Obligation};
+    use crate::graph::decision::tests::assign;
+    use crate::graph::decision::PolicySubgraph;
+    use crate::graph::NodeType;
+
+    const OBLIGATIONS: &str = r#"[
+        {"name": "user-home", "pattern": {"kind": "node_created", "node_type": "User"},
+         "responses": [{"action": "create_node", "node_type": "ObjectAttribute",
+                        "name": "home-{node_name}", "bind": "home"}]},
+        {"name": "admin-object", "pattern": {"kind": "node_created", "node_type": "Object",
+                                             "actor_in": "admins"},
+         "responses": []},
+        {"name": "staff-joined", "pattern": {"kind": "assigned", "node_type": "User",
+                                             "node_in": "staff"},
+         "responses": []}
+    ]"#;
+
+    fn created(actor_id: Option<i64>, node_id: i64, node_type: NodeType) -> GraphEvent {
+        GraphEvent::NodeCreated {
+            actor_id,
+            node_id,
+            node_type,
+            name: format!("node-{}", node_id),
+        }
+    }
+
+    fn assigned(origin_id: i64, target_id: i64) -> GraphEvent {
+        GraphEvent::Assigned {
+            actor_id: Some(100),
+            origin_id,
+            origin_type: NodeType::User,
+            target_id,
+            target_type: NodeType::UserAttribute,
+        }
+    }
+
+    /// 按顺序重放事件，指派事件在变更提交后发出，因此先把边写入图再判断
+    #[test]
+    fn replay_node_created_and_assigned_events() {
+        let obligations: Vec<Obligation> = serde_json::from_str(OBLIGATIONS).unwrap();
+        let attributes = HashMap::from([("admins".to_owned(), 50), ("staff".to_owned(), 60)]);
+        let mut graph = PolicySubgraph::default();
+        graph.insert_edge(assign((NodeType::User, 100), (NodeType::UserAttribute, 50)));
+
+        let steps = [
+            (
+                created(Some(100), 1, NodeType::User),
+                None,
+                vec!["user-home"],
+            ),
+            (
+                created(Some(100), 3, NodeType::Object),
+                None,
+                vec!["admin-object"],
+            ),
+            (created(None, 4, NodeType::Object), None, vec![]),
+            (created(Some(1), 5, NodeType::Object), None, vec![]),
+            (assigned(1, 60), Some((1, 60)), vec!["staff-joined"]),
+            (assigned(2, 70), Some((2, 70)), vec![]),
+            (assigned(1, 70), Some((1, 70)), vec!["staff-joined"]),
+        ];
+        for (event, edge, expected) in steps {
+            if let Some((origin_id, target_id)) = edge {
+                graph.insert_edge(assign(
+                    (NodeType::User, origin_id),
+                    (NodeType::UserAttribute, target_id),
+                ));
+            }
+            let fired: Vec<&str> = obligations
+                .iter()
+                .filter(|o| o.matches_resolved(&graph, &event, &attributes))
+                .map(|o| o.name.as_str())
+                .collect();
+            assert_eq!(fired, expected, "{:?}", event);
+        }
+    }
+
+    #[test]
+    fn missing_attribute_never_matches() {
+        let obligations: Vec<Obligation> = serde_json::from_str(OBLIGATIONS).unwrap();
+        let mut graph = PolicySubgraph::default();
+        graph.insert_edge(assign((NodeType::User, 100), (NodeType::UserAttribute, 50)));
+        let event = created(Some(100), 3, NodeType::Object);
+        assert!(!obligations[1].matches_resolved(&graph, &event, &HashMap::new()));
+    }
+
+    #[test]
+    fn render_placeholders() {
+        let event = created(Some(100), 1, NodeType::User);
+        let context = EventContext {
+            event: &event,
+            node_name: "alice".to_owned(),
+            bindings: HashMap::new(),
+        };
+        assert_eq!(
+            context.render("home-{node_name}-{node_id}-{actor_id}"),
+            "home-alice-1-100"
+        );
+    }
+}
@@ -0,0 +1,1096 @@
+use apache_age::tokio::Client;
+use pilota::AHashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Display;
+use volo_grpc::Status;
+
+use super::access_right::AccessRightSet;
+use super::agtype::rows_to;
+use super::cypher::{CypherQuery, NodePattern, Pattern};
+use super::edge::{list_edges, Edge, EdgeType};
+use super::prohibition::{
+    create_prohibition_tx, delete_prohibition_tx, search_prohibitions, update_prohibition_tx,
+    Prohibition, ProhibitionContainer,
+};
+use super::{
+    assignment_tx, create_node, delete_assignment, delete_node_tx, execute_query, in_transaction,
+    lock_graph, search_node, update_node, Assignment, DeletePolicy, NodeType, NodeTypeObject,
+    Transaction,
+};
+
+/// 策略文档的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyFormat {
+    Json,
+    Yaml,
+}
+
+impl PolicyFormat {
+    /// 按文件扩展名判断，`.json` 以外都视为 YAML
+    pub fn from_path(path: &str) -> PolicyFormat {
+        if path.ends_with(".json") {
+            PolicyFormat::Json
+        } else {
+            PolicyFormat::Yaml
+        }
+    }
+}
+
+/// 文档中以类型和名称引用节点
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NodeRef {
+    #[serde(rename = "type")]
+    pub node_type: NodeType,
+    pub name: String,
+}
+
+impl NodeRef {
+    pub fn new(node_type: NodeType, name: &str) -> Self {
+        Self {
+            node_type,
+            name: name.to_owned(),
+        }
+    }
+}
+
+impl Display for NodeRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.node_type, self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PolicyNode {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PolicyAssignment {
+    pub child: NodeRef,
+    pub parent: NodeRef,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyAssociation {
+    pub user_attribute: String,
+    pub object_attribute: String,
+    pub operations: AccessRightSet,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PolicyProhibitionContainer {
+    pub object_attribute: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub complement: bool,
+}
+
+/// 禁止关系，主体为用户或用户属性，容器为对象属性
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyProhibition {
+    pub name: String,
+    pub subject: NodeRef,
+    pub operations: AccessRightSet,
+    #[serde(default)]
+    pub intersection: bool,
+    pub containers: Vec<PolicyProhibitionContainer>,
+}
+
+impl PolicyProhibition {
+    /// 容器按名称排序去重，比较两个禁止关系时不受书写顺序影响
+    fn normalized(&self) -> PolicyProhibition {
+        let containers: BTreeSet<PolicyProhibitionContainer> =
+            self.containers.iter().cloned().collect();
+        PolicyProhibition {
+            containers: containers.into_iter().collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// 声明式的 NGAC 策略，节点以 (类型, 名称) 标识，不含节点 id
+///
+/// 用户的账号信息保存在 `user_property` 中，文档只引用已存在的用户，不导出用户属性。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyDocument {
+    #[serde(default)]
+    pub policy_classes: Vec<PolicyNode>,
+    #[serde(default)]
+    pub user_attributes: Vec<PolicyNode>,
+    #[serde(default)]
+    pub object_attributes: Vec<PolicyNode>,
+    #[serde(default)]
+    pub objects: Vec<PolicyNode>,
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub assignments: Vec<PolicyAssignment>,
+    #[serde(default)]
+    pub associations: Vec<PolicyAssociation>,
+    #[serde(default)]
+    pub prohibitions: Vec<PolicyProhibition>,
+}
+
+impl PolicyDocument {
+    pub fn parse(text: &str, format: PolicyFormat) -> Result<Self, Status> {
+        match format {
+            PolicyFormat::Json => {
+                serde_json::from_str(text).map_err(|e| Status::invalid_argument(e.to_string()))
+            }
+            PolicyFormat::Yaml => {
+                serde_yaml::from_str(text).map_err(|e| Status::invalid_argument(e.to_string()))
+            }
+        }
+    }
+
+    pub fn render(&self, format: PolicyFormat) -> Result<String, Status> {
+        match format {
+            PolicyFormat::Json => {
+                serde_json::to_string_pretty(self).map_err(|e| Status::internal(e.to_string()))
+            }
+            PolicyFormat::Yaml => {
+                serde_yaml::to_string(self).map_err(|e| Status::internal(e.to_string()))
+            }
+        }
+    }
+
+    /// 除用户外的节点，按创建顺序：策略类、用户属性、对象属性、对象
+    fn nodes(&self) -> impl Iterator<Item = (NodeType, &PolicyNode)> {
+        [
+            (NodeType::PolicyClass, &self.policy_classes),
+            (NodeType::UserAttribute, &self.user_attributes),
+            (NodeType::ObjectAttribute, &self.object_attributes),
+            (NodeType::Object, &self.objects),
+        ]
+        .into_iter()
+        .flat_map(|(node_type, nodes)| nodes.iter().map(move |node| (node_type, node)))
+    }
+
+    fn node_refs(&self) -> HashSet<NodeRef> {
+        self.nodes()
+            .map(|(node_type, node)| NodeRef::new(node_type, &node.name))
+            .chain(
+                self.users
+                    .iter()
+                    .map(|name| NodeRef::new(NodeType::User, name)),
+            )
+            .collect()
+    }
+
+    fn validate(&self) -> Result<(), Status> {
+        let mut seen = HashSet::new();
+        for (node_type, node) in self.nodes() {
+            if !seen.insert(NodeRef::new(node_type, &node.name)) {
+                return Err(Status::invalid_argument(format!(
+                    "duplicate node: {}",
+                    NodeRef::new(node_type, &node.name)
+                )));
+            }
+        }
+
+        let nodes = self.node_refs();
+        let declared = |node: &NodeRef| {
+            if nodes.contains(node) {
+                Ok(())
+            } else {
+                Err(Status::invalid_argument(format!(
+                    "node: {} is not declared!",
+                    node
+                )))
+            }
+        };
+        for PolicyAssignment { child, parent } in self.assignments.iter() {
+            declared(child)?;
+            declared(parent)?;
+            if !child.node_type.can_assign_to(&parent.node_type) {
+                return Err(Status::invalid_argument(format!(
+                    "illegal NGAC assignment: {} -> {}",
+                    child, parent
+                )));
+            }
+        }
+        for association in self.associations.iter() {
+            declared(&NodeRef::new(
+                NodeType::UserAttribute,
+                &association.user_attribute,
+            ))?;
+            declared(&NodeRef::new(
+                NodeType::ObjectAttribute,
+                &association.object_attribute,
+            ))?;
+            if association.operations.is_empty() {
+                return Err(Status::invalid_argument(format!(
+                    "association: {} -> {} has no operations!",
+                    association.user_attribute, association.object_attribute
+                )));
+            }
+            association.operations.validate()?;
+        }
+
+        let mut names = HashSet::new();
+        for prohibition in self.prohibitions.iter() {
+            if prohibition.name.is_empty() {
+                return Err(Status::invalid_argument(
+                    "prohibition name cannot be empty!",
+                ));
+            }
+            if !names.insert(prohibition.name.as_str()) {
+                return Err(Status::invalid_argument(format!(
+                    "duplicate prohibition: {}",
+                    prohibition.name
+                )));
+            }
+            declared(&prohibition.subject)?;
+            if !matches!(
+                prohibition.subject.node_type,
+                NodeType::User | NodeType::UserAttribute
+            ) {
+                return Err(Status::invalid_argument(format!(
+                    "prohibition: {} subject must be a user or user attribute!",
+                    prohibition.name
+                )));
+            }
+            if prohibition.operations.is_empty() {
+                return Err(Status::invalid_argument(format!(
+                    "prohibition: {} has no operations!",
+                    prohibition.name
+                )));
+            }
+            prohibition.operations.validate()?;
+            if prohibition.containers.is_empty() {
+                return Err(Status::invalid_argument(format!(
+                    "prohibition: {} has no containers!",
+                    prohibition.name
+                )));
+            }
+            for container in prohibition.containers.iter() {
+                declared(&NodeRef::new(
+                    NodeType::ObjectAttribute,
+                    &container.object_attribute,
+                ))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 导入时对图的一项变更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyChange {
+    CreateNode {
+        node: NodeRef,
+        properties: BTreeMap<String, String>,
+    },
+    /// 只包含新增或取值不同的属性
+    UpdateNode {
+        node: NodeRef,
+        properties: BTreeMap<String, String>,
+    },
+    DeleteNode {
+        node: NodeRef,
+    },
+    Assign {
+        child: NodeRef,
+        parent: NodeRef,
+    },
+    Unassign {
+        child: NodeRef,
+        parent: NodeRef,
+    },
+    Associate {
+        user_attribute: String,
+        object_attribute: String,
+        operations: AccessRightSet,
+    },
+    UpdateAssociation {
+        user_attribute: String,
+        object_attribute: String,
+        operations: AccessRightSet,
+    },
+    Dissociate {
+        user_attribute: String,
+        object_attribute: String,
+    },
+    CreateProhibition {
+        prohibition: PolicyProhibition,
+    },
+    /// 主体不变，替换操作集、交并方式和容器
+    UpdateProhibition {
+        prohibition: PolicyProhibition,
+    },
+    DeleteProhibition {
+        name: String,
+    },
+}
+
+fn fmt_operations(operations: &AccessRightSet) -> String {
+    operations
+        .iter()
+        .map(|right| right.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for PolicyChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyChange::CreateNode { node, .. } => write!(f, "+ {}", node),
+            PolicyChange::UpdateNode { node, properties } => write!(
+                f,
+                "~ {} {{{}}}",
+                node,
+                properties.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
+            PolicyChange::DeleteNode { node } => write!(f, "- {}", node),
+            PolicyChange::Assign { child, parent } => write!(f, "+ {} -> {}", child, parent),
+            PolicyChange::Unassign { child, parent } => write!(f, "- {} -> {}", child, parent),
+            PolicyChange::Associate {
+                user_attribute,
+                object_attribute,
+                operations,
+            } => write!(
+                f,
+                "+ ua:{} => oa:{} [{}]",
+                user_attribute,
+                object_attribute,
+                fmt_operations(operations)
+            ),
+            PolicyChange::UpdateAssociation {
+                user_attribute,
+                object_attribute,
+                operations,
+            } => write!(
+                f,
+                "~ ua:{} => oa:{} [{}]",
+                user_attribute,
+                object_attribute,
+                fmt_operations(operations)
+            ),
+            PolicyChange::Dissociate {
+                user_attribute,
+                object_attribute,
+            } => write!(f, "- ua:{} => oa:{}", user_attribute, object_attribute),
+            PolicyChange::CreateProhibition { prohibition } => write!(
+                f,
+                "+ prohibition:{} {} [{}]",
+                prohibition.name,
+                prohibition.subject,
+                fmt_operations(&prohibition.operations)
+            ),
+            PolicyChange::UpdateProhibition { prohibition } => write!(
+                f,
+                "~ prohibition:{} {} [{}]",
+                prohibition.name,
+                prohibition.subject,
+                fmt_operations(&prohibition.operations)
+            ),
+            PolicyChange::DeleteProhibition { name } => write!(f, "- prohibition:{}", name),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NodeRecord {
+    id: i64,
+    label: String,
+    #[serde(default)]
+    properties: Value,
+}
+
+/// 当前图的快照，节点以 (类型, 名称) 索引
+#[derive(Default)]
+struct GraphSnapshot {
+    nodes: BTreeMap<NodeRef, BTreeMap<String, String>>,
+    refs: HashMap<i64, NodeRef>,
+    assignments: BTreeSet<PolicyAssignment>,
+    associations: BTreeMap<(String, String), AccessRightSet>,
+    prohibitions: BTreeMap<String, PolicyProhibition>,
+}
+
+fn node_properties(properties: &Value) -> BTreeMap<String, String> {
+    properties
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(k, _)| k.as_str() != "name")
+        .map(|(k, v)| {
+            let v = match v {
+                Value::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            (k.clone(), v)
+        })
+        .collect()
+}
+
+impl GraphSnapshot {
+    async fn load(client: &Client) -> Result<Self, Status> {
+        let query = CypherQuery::new()
+            .match_([Pattern::node(NodePattern::new("n"))])
+            .return_("{id: id(n), label: label(n), properties: properties(n)}");
+        let rows = execute_query(client, query).await?;
+        let mut snapshot = GraphSnapshot::default();
+        for record in rows_to::<NodeRecord>(&rows)? {
+            let (Some(node_type), Some(name)) = (
+                NodeType::from_label(&record.label),
+                record.properties.get("name").and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            let node = NodeRef::new(node_type, name);
+            snapshot
+                .nodes
+                .insert(node.clone(), node_properties(&record.properties));
+            snapshot.refs.insert(record.id, node);
+        }
+
+        let edges: Vec<Edge> = list_edges(
+            client,
+            &[EdgeType::Assignment, EdgeType::Association],
+            None,
+            None,
+        )
+        .await?;
+        for edge in edges {
+            let (Some(child), Some(parent)) = (
+                snapshot.refs.get(&edge.start_id),
+                snapshot.refs.get(&edge.end_id),
+            ) else {
+                continue;
+            };
+            if !edge.edge_type.connects(child.node_type, parent.node_type) {
+                continue;
+            }
+            match edge.edge_type {
+                EdgeType::Assignment => {
+                    snapshot.assignments.insert(PolicyAssignment {
+                        child: child.clone(),
+                        parent: parent.clone(),
+                    });
+                }
+                EdgeType::Association => {
+                    snapshot
+                        .associations
+                        .insert((child.name.clone(), parent.name.clone()), edge.operations());
+                }
+                EdgeType::Prohibition => {}
+            }
+        }
+
+        for prohibition in search_prohibitions(client, None, None).await? {
+            if let Some(prohibition) = snapshot.policy_prohibition(&prohibition) {
+                snapshot
+                    .prohibitions
+                    .insert(prohibition.name.clone(), prohibition);
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// 以名称引用主体和容器，主体不是用户或用户属性时忽略，不是对象属性的容器同样忽略
+    fn policy_prohibition(&self, prohibition: &Prohibition) -> Option<PolicyProhibition> {
+        let subject = self
+            .refs
+            .get(&prohibition.subject_id)
+            .filter(|s| matches!(s.node_type, NodeType::User | NodeType::UserAttribute))?;
+        let containers = prohibition
+            .containers
+            .iter()
+            .filter_map(|container| {
+                let node = self
+                    .refs
+                    .get(&container.id)
+                    .filter(|n| n.node_type == NodeType::ObjectAttribute)?;
+                Some(PolicyProhibitionContainer {
+                    object_attribute: node.name.clone(),
+                    complement: container.complement,
+                })
+            })
+            .collect();
+        let prohibition = PolicyProhibition {
+            name: prohibition.name.clone(),
+            subject: subject.clone(),
+            operations: prohibition.operations.clone(),
+            intersection: prohibition.intersection,
+            containers,
+        };
+        Some(prohibition.normalized())
+    }
+
+    fn document(self) -> PolicyDocument {
+        let mut document = PolicyDocument::default();
+        for (node, properties) in self.nodes {
+            let policy_node = PolicyNode {
+                name: node.name.clone(),
+                properties,
+            };
+            match node.node_type {
+                NodeType::PolicyClass => document.policy_classes.push(policy_node),
+                NodeType::UserAttribute => document.user_attributes.push(policy_node),
+                NodeType::ObjectAttribute => document.object_attributes.push(policy_node),
+                NodeType::Object => document.objects.push(policy_node),
+                NodeType::User => document.users.push(node.name),
+            }
+        }
+        document.assignments = self.assignments.into_iter().collect();
+        document.associations = self
+            .associations
+            .into_iter()
+            .map(
+                |((user_attribute, object_attribute), operations)| PolicyAssociation {
+                    user_attribute,
+                    object_attribute,
+                    operations,
+                },
+            )
+            .collect();
+        document.prohibitions = self.prohibitions.into_values().collect();
+        document
+    }
+}
+
+/// 导出整个 ngac 图，各部分排序后输出以便做版本对比
+pub async fn export_policy(client: &Client) -> Result<PolicyDocument, Status> {
+    Ok(GraphSnapshot::load(client).await?.document())
+}
+
+/// 对比文档与当前图，得出需要执行的变更
+///
+/// `prune` 为 false 时只新增和修改；为 true 时同时删除文档中没有的禁止关系、边和节点（用户除外）。
+pub async fn plan_policy(
+    client: &Client,
+    document: &PolicyDocument,
+    prune: bool,
+) -> Result<Vec<PolicyChange>, Status> {
+    document.validate()?;
+    let snapshot = GraphSnapshot::load(client).await?;
+    for name in document.users.iter() {
+        if !snapshot
+            .nodes
+            .contains_key(&NodeRef::new(NodeType::User, name))
+        {
+            return Err(Status::failed_precondition(format!(
+                "user: {} does not exist, users must be created through InsertUser!",
+                name
+            )));
+        }
+    }
+    Ok(plan_changes(document, &snapshot, prune))
+}
+
+/// 变更的顺序：建节点，删禁止关系和边，建边，建或改禁止关系，最后删节点；
+/// 删除节点前引用它的禁止关系已被删除或改写
+fn plan_changes(
+    document: &PolicyDocument,
+    snapshot: &GraphSnapshot,
+    prune: bool,
+) -> Vec<PolicyChange> {
+    let mut changes = Vec::new();
+    let declared = document.node_refs();
+    for (node_type, node) in document.nodes() {
+        let node_ref = NodeRef::new(node_type, &node.name);
+        match snapshot.nodes.get(&node_ref) {
+            None => changes.push(PolicyChange::CreateNode {
+                node: node_ref,
+                properties: node.properties.clone(),
+            }),
+            Some(current) => {
+                let properties: BTreeMap<String, String> = node
+                    .properties
+                    .iter()
+                    .filter(|(k, v)| current.get(*k) != Some(*v))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                if !properties.is_empty() {
+                    changes.push(PolicyChange::UpdateNode {
+                        node: node_ref,
+                        properties,
+                    });
+                }
+            }
+        }
+    }
+
+    let pruned = |node: &NodeRef| prune && !declared.contains(node);
+    let desired_associations: BTreeMap<(String, String), &AccessRightSet> = document
+        .associations
+        .iter()
+        .map(|a| {
+            (
+                (a.user_attribute.clone(), a.object_attribute.clone()),
+                &a.operations,
+            )
+        })
+        .collect();
+    let desired_prohibitions: BTreeMap<&str, PolicyProhibition> = document
+        .prohibitions
+        .iter()
+        .map(|p| (p.name.as_str(), p.normalized()))
+        .collect();
+    if prune {
+        for name in snapshot.prohibitions.keys() {
+            if !desired_prohibitions.contains_key(name.as_str()) {
+                changes.push(PolicyChange::DeleteProhibition { name: name.clone() });
+            }
+        }
+        for (user_attribute, object_attribute) in snapshot.associations.keys() {
+            let ends = [
+                NodeRef::new(NodeType::UserAttribute, user_attribute),
+                NodeRef::new(NodeType::ObjectAttribute, object_attribute),
+            ];
+            if !desired_associations
+                .contains_key(&(user_attribute.clone(), object_attribute.clone()))
+                && !ends.iter().any(pruned)
+            {
+                changes.push(PolicyChange::Dissociate {
+                    user_attribute: user_attribute.clone(),
+                    object_attribute: object_attribute.clone(),
+                });
+            }
+        }
+        let desired: BTreeSet<&PolicyAssignment> = document.assignments.iter().collect();
+        for edge in snapshot.assignments.iter() {
+            if !desired.contains(edge) && !pruned(&edge.child) && !pruned(&edge.parent) {
+                changes.push(PolicyChange::Unassign {
+                    child: edge.child.clone(),
+                    parent: edge.parent.clone(),
+                });
+            }
+        }
+    }
+
+    for edge in document.assignments.iter() {
+        if !snapshot.assignments.contains(edge) {
+            changes.push(PolicyChange::Assign {
+                child: edge.child.clone(),
+                parent: edge.parent.clone(),
+            });
+        }
+    }
+    for ((user_attribute, object_attribute), operations) in desired_associations {
+        let key = (user_attribute, object_attribute);
+        match snapshot.associations.get(&key) {
+            None => changes.push(PolicyChange::Associate {
+                user_attribute: key.0,
+                object_attribute: key.1,
+                operations: operations.clone(),
+            }),
+            Some(current) if current != operations => {
+                changes.push(PolicyChange::UpdateAssociation {
+                    user_attribute: key.0,
+                    object_attribute: key.1,
+                    operations: operations.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, prohibition) in desired_prohibitions {
+        match snapshot.prohibitions.get(name) {
+            None => changes.push(PolicyChange::CreateProhibition { prohibition }),
+            // 主体不能修改，删除后重建
+            Some(current) if current.subject != prohibition.subject => {
+                changes.push(PolicyChange::DeleteProhibition {
+                    name: name.to_owned(),
+                });
+                changes.push(PolicyChange::CreateProhibition { prohibition });
+            }
+            Some(current) if *current != prohibition => {
+                changes.push(PolicyChange::UpdateProhibition { prohibition })
+            }
+            Some(_) => {}
+        }
+    }
+
+    if prune {
+        for node in snapshot.nodes.keys() {
+            if node.node_type != NodeType::User && !declared.contains(node) {
+                changes.push(PolicyChange::DeleteNode { node: node.clone() });
+            }
+        }
+    }
+    changes
+}
+
+async fn node_id(client: &Client, node: &NodeRef) -> Result<i64, Status> {
+    Ok(search_node(
+        client,
+        node.node_type,
+        Some(&node.name),
+        None,
+        AHashMap::new(),
+    )
+    .await?
+    .id())
+}
+
+async fn edge_assignment(
+    client: &Client,
+    child: &NodeRef,
+    parent: &NodeRef,
+    operations: AccessRightSet,
+) -> Result<Assignment, Status> {
+    Assignment::from_node_types(
+        child.node_type,
+        node_id(client, child).await?,
+        parent.node_type,
+        node_id(client, parent).await?,
+        operations,
+    )
+    .ok_or_else(|| {
+        Status::failed_precondition(format!("illegal NGAC relation: {} -> {}", child, parent))
+    })
+}
+
+async fn prohibition_containers(
+    client: &Client,
+    prohibition: &PolicyProhibition,
+) -> Result<Vec<ProhibitionContainer>, Status> {
+    let mut containers = Vec::with_capacity(prohibition.containers.len());
+    for container in prohibition.containers.iter() {
+        let node = NodeRef::new(NodeType::ObjectAttribute, &container.object_attribute);
+        containers.push(ProhibitionContainer {
+            id: node_id(client, &node).await?,
+            complement: container.complement,
+        });
+    }
+    Ok(containers)
+}
+
+fn association_ends(user_attribute: &str, object_attribute: &str) -> (NodeRef, NodeRef) {
+    (
+        NodeRef::new(NodeType::UserAttribute, user_attribute),
+        NodeRef::new(NodeType::ObjectAttribute, object_attribute),
+    )
+}
+
+async fn apply_change(tx: &Transaction<'_>, change: &PolicyChange) -> Result<(), Status> {
+    let client: &Client = tx;
+    match change {
+        PolicyChange::CreateNode { node, properties } => {
+            let properties = properties
+                .iter()
+                .map(|(k, v)| (k.clone().into(), v.clone().into()))
+                .collect();
+            let node = NodeTypeObject::new(node.node_type, node.name.clone(), properties);
+            if let Some(s) = create_node(client, node).await {
+                return Err(s);
+            }
+        }
+        PolicyChange::UpdateNode { node, properties } => {
+            let id = node_id(client, node).await?;
+            let properties = properties
+                .iter()
+                .map(|(k, v)| (k.clone().into(), v.clone().into()))
+                .collect();
+            update_node(client, node.node_type, id, None, properties).await?;
+        }
+        PolicyChange::DeleteNode { node } => {
+            let id = node_id(client, node).await?;
+            delete_node_tx(tx, node.node_type, id, DeletePolicy::Cascade).await?;
+        }
+        PolicyChange::Assign { child, parent } => {
+            let edge = edge_assignment(client, child, parent, AccessRightSet::new()).await?;
+            assignment_tx(tx, edge).await?;
+        }
+        PolicyChange::Unassign { child, parent } => {
+            let edge = edge_assignment(client, child, parent, AccessRightSet::new()).await?;
+            delete_assignment(client, edge).await?;
+        }
+        PolicyChange::Associate {
+            user_attribute,
+            object_attribute,
+            operations,
+        } => {
+            let (ua, oa) = association_ends(user_attribute, object_attribute);
+            let edge = edge_assignment(client, &ua, &oa, operations.clone()).await?;
+            assignment_tx(tx, edge).await?;
+        }
+        PolicyChange::UpdateAssociation {
+            user_attribute,
+            object_attribute,
+            operations,
+        } => {
+            let (ua, oa) = association_ends(user_attribute, object_attribute);
+            let edge = edge_assignment(client, &ua, &oa, AccessRightSet::new()).await?;
+            delete_assignment(client, edge).await?;
+            let edge = edge_assignment(client, &ua, &oa, operations.clone()).await?;
+            assignment_tx(tx, edge).await?;
+        }
+        PolicyChange::Dissociate {
+            user_attribute,
+            object_attribute,
+        } => {
+            let (ua, oa) = association_ends(user_attribute, object_attribute);
+            let edge = edge_assignment(client, &ua, &oa, AccessRightSet::new()).await?;
+            delete_assignment(client, edge).await?;
+        }
+        PolicyChange::CreateProhibition { prohibition } => {
+            let created = Prohibition {
+                name: prohibition.name.clone(),
+                subject_id: node_id(client, &prohibition.subject).await?,
+                operations: prohibition.operations.clone(),
+                intersection: prohibition.intersection,
+                containers: prohibition_containers(client, prohibition).await?,
+            };
+            create_prohibition_tx(tx, &created).await?;
+        }
+        PolicyChange::UpdateProhibition { prohibition } => {
+            let containers = prohibition_containers(client, prohibition).await?;
+            update_prohibition_tx(
+                tx,
+                &prohibition.name,
+                Some(prohibition.operations.clone()),
+                Some(prohibition.intersection),
+                Some(containers),
+            )
+            .await?;
+        }
+        PolicyChange::DeleteProhibition { name } => {
+            delete_prohibition_tx(tx, name).await?;
+        }
+    }
+    Ok(())
+}
+
+/// 把文档差量应用到图上，重复导入同一文档不会产生变更；`dry_run` 时只返回变更不执行
+///
+/// 全部变更在一个事务中执行，任一步失败时整体回滚。
+pub async fn import_policy(
+    client: &Client,
+    document: &PolicyDocument,
+    prune: bool,
+    dry_run: bool,
+) -> Result<Vec<PolicyChange>, Status> {
+    in_transaction(client, async |tx| {
+        // 读取快照、计算差量和执行都在图锁之内，差量不会基于已被并发修改的快照
+        lock_graph(tx).await?;
+        let changes = plan_policy(tx, document, prune).await?;
+        if !dry_run {
+            for change in changes.iter() {
+                apply_change(tx, change).await?;
+            }
+        }
+        Ok(changes)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        plan_changes, GraphSnapshot, NodeRef, PolicyChange, PolicyDocument, PolicyFormat,
+        PolicyProhibition,
+    };
+    use crate::graph::access_right::AccessRightSet;
+    use crate::graph::NodeType;
+
+    fn document(operations: &str) -> PolicyDocument {
+        let text = format!(
+            "policy_classes: [{{name: pc}}]
+user_attributes: [{{name: staff}}]
+object_attributes: [{{name: docs}}]
+assignments:
+  - {{child: {{type: UserAttribute, name: staff}}, parent: {{type: PolicyClass, name: pc}}}}
+  - {{child: {{type: ObjectAttribute, name: docs}}, parent: {{type: PolicyClass, name: pc}}}}
+associations:
+  - {{user_attribute: staff, object_attribute: docs, operations: {}}}
+",
+            operations
+        );
+        PolicyDocument::parse(&text, PolicyFormat::Yaml).unwrap()
+    }
+
+    /// staff 不能读写 secret 以及 docs 之外的对象
+    const PROHIBITED: &str = "policy_classes: [{name: pc}]
+user_attributes: [{name: staff}]
+object_attributes: [{name: docs}, {name: secret}]
+users: [alice]
+assignments:
+  - {child: {type: User, name: alice}, parent: {type: UserAttribute, name: staff}}
+  - {child: {type: UserAttribute, name: staff}, parent: {type: PolicyClass, name: pc}}
+  - {child: {type: ObjectAttribute, name: docs}, parent: {type: PolicyClass, name: pc}}
+  - {child: {type: ObjectAttribute, name: secret}, parent: {type: PolicyClass, name: pc}}
+associations:
+  - {user_attribute: staff, object_attribute: docs, operations: [read, write]}
+prohibitions:
+  - name: staff-secret
+    subject: {type: UserAttribute, name: staff}
+    operations: [read, write]
+    containers:
+      - {object_attribute: secret}
+      - {object_attribute: docs, complement: true}
+";
+
+    fn prohibited() -> PolicyDocument {
+        PolicyDocument::parse(PROHIBITED, PolicyFormat::Yaml).unwrap()
+    }
+
+    /// 按文档内容构造的当前图
+    fn snapshot(document: &PolicyDocument) -> GraphSnapshot {
+        let mut snapshot = GraphSnapshot::default();
+        for (node_type, node) in document.nodes() {
+            snapshot
+                .nodes
+                .insert(NodeRef::new(node_type, &node.name), node.properties.clone());
+        }
+        for name in document.users.iter() {
+            snapshot
+                .nodes
+                .insert(NodeRef::new(NodeType::User, name), Default::default());
+        }
+        snapshot.assignments = document.assignments.iter().cloned().collect();
+        for association in document.associations.iter() {
+            snapshot.associations.insert(
+                (
+                    association.user_attribute.clone(),
+                    association.object_attribute.clone(),
+                ),
+                association.operations.clone(),
+            );
+        }
+        for prohibition in document.prohibitions.iter() {
+            snapshot
+                .prohibitions
+                .insert(prohibition.name.clone(), prohibition.normalized());
+        }
+        snapshot
+    }
+
+    fn prohibition(document: &mut PolicyDocument) -> &mut PolicyProhibition {
+        &mut document.prohibitions[0]
+    }
+
+    #[test]
+    fn association_requires_operations() {
+        assert!(document("[read]").validate().is_ok());
+        assert!(document("[]").validate().is_err());
+    }
+
+    #[test]
+    fn prohibitions_are_validated() {
+        assert!(prohibited().validate().is_ok());
+
+        let invalid: [fn(&mut PolicyDocument); 5] = [
+            |d| prohibition(d).name.clear(),
+            |d| prohibition(d).subject = NodeRef::new(NodeType::ObjectAttribute, "docs"),
+            |d| prohibition(d).operations = Default::default(),
+            |d| prohibition(d).containers.clear(),
+            |d| prohibition(d).containers[0].object_attribute = "missing".to_owned(),
+        ];
+        for change in invalid {
+            let mut document = prohibited();
+            change(&mut document);
+            assert!(document.validate().is_err());
+        }
+
+        let mut document = prohibited();
+        document.prohibitions.push(document.prohibitions[0].clone());
+        assert!(document.validate().is_err());
+    }
+
+    #[test]
+    fn exported_prohibitions_round_trip() {
+        let document = snapshot(&prohibited()).document();
+        assert_eq!(document.prohibitions.len(), 1);
+        assert_eq!(
+            document.prohibitions[0].containers[0].object_attribute,
+            "docs"
+        );
+        for format in [PolicyFormat::Json, PolicyFormat::Yaml] {
+            let text = document.render(format).unwrap();
+            assert_eq!(PolicyDocument::parse(&text, format).unwrap(), document);
+        }
+        for prune in [false, true] {
+            assert!(plan_changes(&document, &snapshot(&prohibited()), prune).is_empty());
+        }
+    }
+
+    #[test]
+    fn prohibitions_are_created_and_updated() {
+        let mut current = prohibited();
+        current.prohibitions.clear();
+        let changes = plan_changes(&prohibited(), &snapshot(&current), false);
+        assert_eq!(
+            changes,
+            [PolicyChange::CreateProhibition {
+                prohibition: prohibited().prohibitions[0].normalized()
+            }]
+        );
+
+        let mut desired = prohibited();
+        prohibition(&mut desired).operations = AccessRightSet::from(&["read"][..]);
+        let changes = plan_changes(&desired, &snapshot(&prohibited()), false);
+        assert_eq!(
+            changes,
+            [PolicyChange::UpdateProhibition {
+                prohibition: desired.prohibitions[0].normalized()
+            }]
+        );
+
+        let mut desired = prohibited();
+        prohibition(&mut desired).subject = NodeRef::new(NodeType::User, "alice");
+        let changes = plan_changes(&desired, &snapshot(&prohibited()), false);
+        assert_eq!(
+            changes,
+            [
+                PolicyChange::DeleteProhibition {
+                    name: "staff-secret".to_owned()
+                },
+                PolicyChange::CreateProhibition {
+                    prohibition: desired.prohibitions[0].normalized()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn prune_deletes_prohibitions_before_nodes() {
+        let mut desired = prohibited();
+        desired.prohibitions.clear();
+        desired.object_attributes.retain(|oa| oa.name != "secret");
+        desired.assignments.retain(|a| a.child.name != "secret");
+
+        let changes = plan_changes(&desired, &snapshot(&prohibited()), false);
+        assert!(changes.is_empty());
+
+        let changes = plan_changes(&desired, &snapshot(&prohibited()), true);
+        assert_eq!(
+            changes,
+            [
+                PolicyChange::DeleteProhibition {
+                    name: "staff-secret".to_owned()
+                },
+                PolicyChange::DeleteNode {
+                    node: NodeRef::new(NodeType::ObjectAttribute, "secret")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pruned_container_is_rewritten_before_deletion() {
+        let mut desired = prohibited();
+        desired.object_attributes.retain(|oa| oa.name != "secret");
+        desired.assignments.retain(|a| a.child.name != "secret");
+        prohibition(&mut desired)
+            .containers
+            .retain(|c| c.object_attribute != "secret");
+
+        let changes = plan_changes(&desired, &snapshot(&prohibited()), true);
+        assert_eq!(
+            changes,
+            [
+                PolicyChange::UpdateProhibition {
+                    prohibition: desired.prohibitions[0].normalized()
+                },
+                PolicyChange::DeleteNode {
+                    node: NodeRef::new(NodeType::ObjectAttribute, "secret")
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use apache_age::tokio::Client;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use volo_grpc::Status;
+
+use super::access_right::AccessRightSet;
+use super::agtype::rows_to;
+use super::cypher::{Condition, CypherQuery, EdgePattern, NodePattern, Pattern};
+use super::decision::{EdgeRecord, EDGE_RECORD};
+use super::edge::EdgeType;
+use super::notify::{notify_changes, ChangeKind, GraphChange};
+use super::{
+    execute_query, in_transaction, lock_graph, search_node_types, search_resource_types, NodeType,
+    Transaction, OPERATIONS, PROHIBITION,
+};
+
+pub const INTERSECTION: &str = "intersection";
+pub const COMPLEMENT: &str = "complement";
+
+/// 禁止关系作用的容器，`complement` 为 true 时表示容器之外的资源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProhibitionContainer {
+    pub id: i64,
+    pub complement: bool,
+}
+
+/// NGAC 禁止关系：主体（U 或 UA）不能对容器范围内的资源执行指定操作
+///
+/// 存储为主体到每个容器的一条 `Prohibition` 边，同一禁止关系的边共享 name、操作集和交并方式。
+#[derive(Debug, Clone)]
+pub struct Prohibition {
+    pub name: String,
+    pub subject_id: i64,
+    pub operations: AccessRightSet,
+    /// true 时资源需满足全部容器条件（交集），false 时满足任一即可（并集）
+    pub intersection: bool,
+    pub containers: Vec<ProhibitionContainer>,
+}
+
+impl Prohibition {
+    /// 资源（以其指派闭包表示）是否落在禁止范围内
+    pub fn covers(&self, resource_closure: &HashSet<i64>) -> bool {
+        let mut matched = self
+            .containers
+            .iter()
+            .map(|c| resource_closure.contains(&c.id) != c.complement);
+        if self.containers.is_empty() {
+            false
+        } else if self.intersection {
+            matched.all(|m| m)
+        } else {
+            matched.any(|m| m)
+        }
+    }
+
+    /// 按 name 将 `Prohibition` 边聚合为禁止关系
+    pub fn from_edges<'a>(edges: impl IntoIterator<Item = &'a EdgeRecord>) -> Vec<Prohibition> {
+        let mut prohibitions: BTreeMap<String, Prohibition> = BTreeMap::new();
+        for edge in edges.into_iter().filter(|e| e.is_prohibition()) {
+            let Some(name) = edge.properties.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let prohibition = prohibitions
+                .entry(name.to_owned())
+                .or_insert_with(|| Prohibition {
+                    name: name.to_owned(),
+                    subject_id: edge.start_id,
+                    operations: edge.operations(),
+                    intersection: edge
+                        .properties
+                        .get(INTERSECTION)
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false),
+                    containers: Vec::new(),
+                });
+            prohibition.containers.push(ProhibitionContainer {
+                id: edge.end_id,
+                complement: edge
+                    .properties
+                    .get(COMPLEMENT)
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            });
+        }
+        prohibitions.into_values().collect()
+    }
+
+    async fn validate(&self, client: &Client) -> Result<(), Status> {
+        if self.name.is_empty() {
+            return Err(Status::invalid_argument(
+                "prohibition name cannot be empty!",
+            ));
+        }
+        if self.operations.is_empty() {
+            return Err(Status::invalid_argument(
+                "prohibition operations cannot be empty!",
+            ));
+        }
+        if self.containers.is_empty() {
+            return Err(Status::invalid_argument(
+                "prohibition containers cannot be empty!",
+            ));
+        }
+
+        let mut ids = vec![self.subject_id];
+        ids.extend(self.containers.iter().map(|c| c.id));
+        let node_types = search_node_types(client, &ids).await?;
+        match node_types.get(&self.subject_id) {
+            Some(NodeType::User) | Some(NodeType::UserAttribute) => {}
+            Some(_) => {
+                return Err(Status::failed_precondition(
+                    "prohibition subject must be a user or user attribute!",
+                ))
+            }
+            None => return Err(Status::not_found("subject not found!")),
+        }
+        for container in self.containers.iter() {
+            match node_types.get(&container.id) {
+                Some(NodeType::ObjectAttribute) => {}
+                Some(_) => {
+                    return Err(Status::failed_precondition(
+                        "prohibition container must be an object attribute!",
+                    ))
+                }
+                None => return Err(Status::not_found("container not found!")),
+            }
+        }
+        // 禁止的操作需为某个容器的资源类型所支持
+        let containers: Vec<i64> = self.containers.iter().map(|c| c.id).collect();
+        let resource_types: Vec<String> = search_resource_types(client, &containers)
+            .await?
+            .into_values()
+            .collect();
+        self.operations.validate_for(&resource_types)?;
+        Ok(())
+    }
+
+    /// 每个容器对应一条主体→容器的禁止边
+    fn changes(&self, kind: ChangeKind) -> impl Iterator<Item = GraphChange> + '_ {
+        self.containers.iter().map(move |container| {
+            GraphChange::edge(kind, EdgeType::Prohibition, self.subject_id, container.id)
+        })
+    }
+
+    async fn insert(&self, client: &Client) -> Result<(), Status> {
+        for container in self.containers.iter() {
+            let edge = EdgePattern::new()
+                .alias("r")
+                .label(PROHIBITION)
+                .property("name", self.name.as_str())
+                .property(OPERATIONS, self.operations.to_value())
+                .property(INTERSECTION, self.intersection)
+                .property(COMPLEMENT, container.complement);
+            let query = CypherQuery::new()
+                .match_([
+                    Pattern::node(NodePattern::new("origin")),
+                    Pattern::node(NodePattern::new("target")),
+                ])
+                .where_([
+                    Condition::id("origin", self.subject_id),
+                    Condition::id("target", container.id),
+                ])
+                .create([
+                    Pattern::node(NodePattern::new("origin")).to(edge, NodePattern::new("target"))
+                ])
+                .return_("r");
+            execute_query(client, query).await?;
+        }
+        notify_changes(client, self.changes(ChangeKind::Created)).await
+    }
+}
+
+fn prohibition_edges_cypher(subject_id: Option<i64>, name: Option<&str>) -> CypherQuery {
+    let mut conditions = Vec::new();
+    if let Some(subject_id) = subject_id {
+        conditions.push(Condition::id("origin", subject_id));
+    }
+    if let Some(name) = name {
+        conditions.push(Condition::property("r", "name", name));
+    }
+    CypherQuery::new()
+        .match_([Pattern::node(NodePattern::new("origin")).to(
+            EdgePattern::new().alias("r").label(PROHIBITION),
+            NodePattern::new("target"),
+        )])
+        .where_(conditions)
+}
+
+/// 按主体或名称查询禁止关系，都为空时返回全部
+pub async fn search_prohibitions(
+    client: &Client,
+    subject_id: Option<i64>,
+    name: Option<&str>,
+) -> Result<Vec<Prohibition>, Status> {
+    let query = prohibition_edges_cypher(subject_id, name).return_(EDGE_RECORD);
+    let rows = execute_query(client, query).await?;
+    let edges: Vec<EdgeRecord> = rows_to(&rows)?;
+    Ok(Prohibition::from_edges(edges.iter()))
+}
+
+async fn search_prohibition(client: &Client, name: &str) -> Result<Prohibition, Status> {
+    search_prohibitions(client, None, Some(name))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Status::not_found(format!("prohibition: {} not found!", name)))
+}
+
+pub async fn create_prohibition(client: &Client, prohibition: &Prohibition) -> Result<(), Status> {
+    in_transaction(client, async |tx| {
+        create_prohibition_tx(tx, prohibition).await
+    })
+    .await
+}
+
+/// 在调用方的事务中新建禁止关系，见 [`create_prohibition`]
+///
+/// 校验与写入之间持有图锁，引用的节点不会在校验之后被并发删除。
+pub async fn create_prohibition_tx(
+    tx: &Transaction<'_>,
+    prohibition: &Prohibition,
+) -> Result<(), Status> {
+    lock_graph(tx).await?;
+    prohibition.validate(tx).await?;
+    if !search_prohibitions(tx, None, Some(&prohibition.name))
+        .await?
+        .is_empty()
+    {
+        return Err(Status::already_exists(format!(
+            "prohibition: {} exists!",
+            prohibition.name
+        )));
+    }
+    prohibition.insert(tx).await
+}
+
+/// 修改禁止关系，参数为 None 的部分保持不变；以删除后重建的方式整体替换所有边
+pub async fn update_prohibition(
+    client: &Client,
+    name: &str,
+    operations: Option<AccessRightSet>,
+    intersection: Option<bool>,
+    containers: Option<Vec<ProhibitionContainer>>,
+) -> Result<Prohibition, Status> {
+    in_transaction(client, async |tx| {
+        update_prohibition_tx(tx, name, operations, intersection, containers).await
+    })
+    .await
+}
+
+/// 在调用方的事务中修改禁止关系，见 [`update_prohibition`]
+pub async fn update_prohibition_tx(
+    tx: &Transaction<'_>,
+    name: &str,
+    operations: Option<AccessRightSet>,
+    intersection: Option<bool>,
+    containers: Option<Vec<ProhibitionContainer>>,
+) -> Result<Prohibition, Status> {
+    lock_graph(tx).await?;
+    let mut prohibition = search_prohibition(tx, name).await?;
+    let previous = prohibition.clone();
+    if let Some(operations) = operations {
+        prohibition.operations = operations;
+    }
+    if let Some(intersection) = intersection {
+        prohibition.intersection = intersection;
+    }
+    if let Some(containers) = containers {
+        prohibition.containers = containers;
+    }
+    prohibition.validate(tx).await?;
+
+    let query = prohibition_edges_cypher(None, Some(name)).delete(&["r"], false);
+    execute_query(tx, query).await?;
+    notify_changes(tx, previous.changes(ChangeKind::Deleted)).await?;
+    prohibition.insert(tx).await?;
+    Ok(prohibition)
+}
+
+pub async fn delete_prohibition(client: &Client, name: &str) -> Result<(), Status> {
+    in_transaction(client, async |tx| delete_prohibition_tx(tx, name).await).await
+}
+
+/// 在调用方的事务中删除禁止关系，见 [`delete_prohibition`]
+pub async fn delete_prohibition_tx(tx: &Transaction<'_>, name: &str) -> Result<(), Status> {
+    lock_graph(tx).await?;
+    let prohibition = search_prohibition(tx, name).await?;
+    let query = prohibition_edges_cypher(None, Some(name)).delete(&["r"], false);
+    execute_query(tx, query).await?;
+    notify_changes(tx, prohibition.changes(ChangeKind::Deleted)).await
+}
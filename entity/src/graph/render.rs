@@ -0,0 +1,282 @@
+use apache_age::tokio::Client;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use volo_grpc::Status;
+
+use super::agtype::rows_to;
+use super::cypher::{Condition, CypherQuery, EdgePattern, NodePattern, Pattern};
+use super::decision::{fetch_out_edges, EdgeRecord, EDGE_RECORD};
+use super::edge::EdgeType;
+use super::{execute_query, NodeType};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphNode {
+    pub id: i64,
+    pub label: String,
+    #[serde(default)]
+    pub name: String,
+}
+
+impl GraphNode {
+    pub fn node_type(&self) -> Option<NodeType> {
+        NodeType::from_label(&self.label)
+    }
+}
+
+/// 节点按类型着色
+pub fn node_color(node_type: Option<NodeType>) -> &'static str {
+    match node_type {
+        Some(NodeType::User) => "#4e79a7",
+        Some(NodeType::UserAttribute) => "#76b7b2",
+        Some(NodeType::Object) => "#f28e2b",
+        Some(NodeType::ObjectAttribute) => "#edc948",
+        Some(NodeType::PolicyClass) => "#59a14f",
+        None => "#bab0ac",
+    }
+}
+
+/// 关联和禁止边显示其访问权限，指派边不显示
+fn edge_text(edge: &EdgeRecord) -> String {
+    let operations = edge
+        .operations()
+        .iter()
+        .map(|right| right.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    match edge.edge_type() {
+        Some(EdgeType::Association) => operations,
+        Some(EdgeType::Prohibition) => format!("deny: {}", operations),
+        _ => String::new(),
+    }
+}
+
+/// 换行也转义为 `\n`，原样的换行会截断带引号的标签
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// 用于渲染的图：全图或从某节点出发沿出边可达的子图
+#[derive(Debug, Clone, Default)]
+pub struct GraphView {
+    pub nodes: BTreeMap<i64, GraphNode>,
+    pub edges: Vec<EdgeRecord>,
+}
+
+const GRAPH_NODE: &str = "{id: id(n), label: label(n), name: n.name}";
+
+impl GraphView {
+    pub async fn full(client: &Client) -> Result<Self, Status> {
+        let query = CypherQuery::new()
+            .match_([Pattern::node(NodePattern::new("n"))])
+            .return_(GRAPH_NODE);
+        let nodes: Vec<GraphNode> = rows_to(&execute_query(client, query).await?)?;
+
+        let query = CypherQuery::new()
+            .match_([Pattern::node(NodePattern::new("origin"))
+                .to(EdgePattern::new().alias("r"), NodePattern::new("target"))])
+            .return_(EDGE_RECORD);
+        let edges: Vec<EdgeRecord> = rows_to(&execute_query(client, query).await?)?;
+        Ok(GraphView {
+            nodes: nodes.into_iter().map(|node| (node.id, node)).collect(),
+            edges,
+        })
+    }
+
+    /// 从起点沿所有类型的出边展开：用户可看到其属性、策略类以及关联和禁止作用的对象属性，
+    /// 对象可看到其所属的属性和策略类
+    pub async fn reachable(client: &Client, start_id: i64) -> Result<Self, Status> {
+        let mut visited = HashSet::from([start_id]);
+        let mut frontier = vec![start_id];
+        let mut edges = Vec::new();
+        while !frontier.is_empty() {
+            let out_edges = fetch_out_edges(client, &frontier, &[]).await?;
+            frontier = Vec::new();
+            for edge in out_edges {
+                if visited.insert(edge.end_id) {
+                    frontier.push(edge.end_id);
+                }
+                edges.push(edge);
+            }
+        }
+
+        let query = CypherQuery::new()
+            .match_([Pattern::node(NodePattern::new("n"))])
+            .where_([Condition::id_in("n", visited.into_iter().collect())])
+            .return_(GRAPH_NODE);
+        let nodes: Vec<GraphNode> = rows_to(&execute_query(client, query).await?)?;
+        if !nodes.iter().any(|node| node.id == start_id) {
+            return Err(Status::not_found(format!("node: {} not found!", start_id)));
+        }
+        Ok(GraphView {
+            nodes: nodes.into_iter().map(|node| (node.id, node)).collect(),
+            edges,
+        })
+    }
+
+    /// Graphviz DOT，指派边向上指向父节点
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph ngac {\n    rankdir=BT;\n    node [style=filled];\n");
+        for node in self.nodes.values() {
+            let node_type = node.node_type().map(|t| t.to_string()).unwrap_or_default();
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\\n({})\", fillcolor=\"{}\"];",
+                node.id,
+                escape_dot(&node.name),
+                node_type,
+                node_color(node.node_type())
+            );
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.edge_type() {
+                Some(EdgeType::Association) => ", style=dashed",
+                Some(EdgeType::Prohibition) => ", style=dashed, color=red, fontcolor=red",
+                _ => "",
+            };
+            let _ = writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\"{}];",
+                edge.start_id,
+                edge.end_id,
+                escape_dot(&edge_text(edge)),
+                style
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n",
+            "  <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n",
+            "  <key id=\"color\" for=\"node\" attr.name=\"color\" attr.type=\"string\"/>\n",
+            "  <key id=\"edge_type\" for=\"edge\" attr.name=\"edge_type\" attr.type=\"string\"/>\n",
+            "  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <graph id=\"ngac\" edgedefault=\"directed\">\n",
+        ));
+        for node in self.nodes.values() {
+            let _ = writeln!(
+                xml,
+                "    <node id=\"n{}\"><data key=\"name\">{}</data><data key=\"type\">{}</data><data key=\"color\">{}</data></node>",
+                node.id,
+                escape_xml(&node.name),
+                escape_xml(&node.label),
+                node_color(node.node_type())
+            );
+        }
+        for (i, edge) in self.edges.iter().enumerate() {
+            let _ = writeln!(
+                xml,
+                "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"><data key=\"edge_type\">{}</data><data key=\"label\">{}</data></edge>",
+                i,
+                edge.start_id,
+                edge.end_id,
+                escape_xml(&edge.label),
+                escape_xml(&edge_text(edge))
+            );
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{GraphNode, GraphView};
+    use crate::graph::decision::tests::{assign, edge};
+    use crate::graph::{NodeType, ASSOCIATION, PROHIBITION};
+
+    fn node(id: i64, label: &str, name: &str) -> GraphNode {
+        GraphNode {
+            id,
+            label: label.to_owned(),
+            name: name.to_owned(),
+        }
+    }
+
+    /// u1 → ua2 -[read]-> oa4，ua2 -[deny: write]-> oa4
+    fn view(user_name: &str) -> GraphView {
+        let ua2 = (NodeType::UserAttribute, 2);
+        let oa4 = (NodeType::ObjectAttribute, 4);
+        GraphView {
+            nodes: [
+                node(1, "User", user_name),
+                node(2, "UserAttribute", "staff"),
+                node(4, "ObjectAttribute", "docs"),
+                node(9, "Unknown", "other"),
+            ]
+            .into_iter()
+            .map(|node| (node.id, node))
+            .collect(),
+            edges: vec![
+                assign((NodeType::User, 1), ua2),
+                edge(ua2, ASSOCIATION, oa4, json!({"operations": ["read"]})),
+                edge(
+                    ua2,
+                    PROHIBITION,
+                    oa4,
+                    json!({"name": "deny", "operations": ["write"]}),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn dot_colors_nodes_and_labels_edges() {
+        let dot = view("alice").to_dot();
+        assert!(dot.starts_with("digraph ngac {\n"));
+        assert!(dot.contains("    n1 [label=\"alice\\n(u)\", fillcolor=\"#4e79a7\"];\n"));
+        assert!(dot.contains("    n4 [label=\"docs\\n(oa)\", fillcolor=\"#edc948\"];\n"));
+        assert!(dot.contains("    n9 [label=\"other\\n()\", fillcolor=\"#bab0ac\"];\n"));
+        assert!(dot.contains("    n1 -> n2 [label=\"\"];\n"));
+        assert!(dot.contains("    n2 -> n4 [label=\"read\", style=dashed];\n"));
+        assert!(dot.contains(
+            "    n2 -> n4 [label=\"deny: write\", style=dashed, color=red, fontcolor=red];\n"
+        ));
+    }
+
+    #[test]
+    fn dot_escapes_quotes_backslashes_and_newlines() {
+        let dot = view("a\"b\\c\nd\r\ne").to_dot();
+        assert!(dot.contains("    n1 [label=\"a\\\"b\\\\c\\nd\\ne\\n(u)\""));
+        assert_eq!(
+            dot.lines()
+                .filter(|line| line.starts_with("    n1 ["))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn graphml_escapes_names_and_labels_edges() {
+        let xml = view("<a & 'b'>").to_graphml();
+        assert!(xml.contains(concat!(
+            "    <node id=\"n1\"><data key=\"name\">&lt;a &amp; &apos;b&apos;&gt;</data>",
+            "<data key=\"type\">User</data><data key=\"color\">#4e79a7</data></node>\n"
+        )));
+        assert!(xml.contains(concat!(
+            "    <edge id=\"e2\" source=\"n2\" target=\"n4\"><data key=\"edge_type\">Prohibition</data>",
+            "<data key=\"label\">deny: write</data></edge>\n"
+        )));
+        assert!(xml
+            .contains("<data key=\"edge_type\">Association</data><data key=\"label\">read</data>"));
+        assert!(xml.ends_with("  </graph>\n</graphml>\n"));
+    }
+}
@@ -0,0 +1,394 @@
+use apache_age::tokio::Client;
+use std::collections::{BTreeSet, HashMap};
+use volo_grpc::Status;
+
+use super::access_right::AccessRightSet;
+use super::agtype::rows_to;
+use super::decision::{fetch_in_edges, EdgeRecord, PolicySubgraph};
+use super::edge::EdgeType;
+use super::{
+    execute_query, search_node_types, search_resource_types,
+    search_target_ids_to_assigned_origin_node_path_cypher, NodeType,
+};
+
+/// 一条授权推导路径：用户 →* UA → OA ←* 资源
+///
+/// `edges[i]` 连接 `nodes[i]` 和 `nodes[i + 1]`，资源一侧的指派边方向与路径方向相反。
+#[derive(Debug, Clone)]
+pub struct DerivationPath {
+    pub nodes: Vec<i64>,
+    pub edges: Vec<EdgeType>,
+    /// 该路径上的关联授予且最终生效的操作
+    pub operations: AccessRightSet,
+}
+
+/// 用户对资源的有效访问权限及其推导路径
+#[derive(Debug, Clone)]
+pub struct AccessEntry {
+    pub user_id: i64,
+    pub resource_id: i64,
+    pub operations: AccessRightSet,
+    pub paths: Vec<DerivationPath>,
+}
+
+/// 沿指派关系向下查找所有（直接或间接）指派到 ids 的指定类型节点
+async fn assigned_descendants(
+    client: &Client,
+    ids: &[i64],
+    node_type: NodeType,
+) -> Result<BTreeSet<i64>, Status> {
+    if ids.is_empty() {
+        return Ok(BTreeSet::new());
+    }
+    let query = search_target_ids_to_assigned_origin_node_path_cypher(node_type, ids.to_vec());
+    let found: Vec<i64> = rows_to(&execute_query(client, query).await?)?;
+    Ok(found.into_iter().collect())
+}
+
+async fn expect_node_type(
+    client: &Client,
+    id: i64,
+    expected: &[NodeType],
+) -> Result<NodeType, Status> {
+    match search_node_types(client, &[id]).await?.get(&id) {
+        Some(node_type) if expected.contains(node_type) => Ok(*node_type),
+        Some(node_type) => Err(Status::invalid_argument(format!(
+            "node: {} is a {:?}!",
+            id, node_type
+        ))),
+        None => Err(Status::not_found(format!("node: {} not found!", id))),
+    }
+}
+
+fn derivation_path(
+    graph: &PolicySubgraph,
+    user_id: i64,
+    resource_id: i64,
+    association: &EdgeRecord,
+    operations: AccessRightSet,
+) -> Option<DerivationPath> {
+    let user_path = graph.assignment_path(user_id, association.start_id)?;
+    let resource_path = graph.assignment_path(resource_id, association.end_id)?;
+    let mut edges = vec![EdgeType::Assignment; user_path.len() - 1];
+    edges.push(EdgeType::Association);
+    edges.extend(vec![EdgeType::Assignment; resource_path.len() - 1]);
+    let mut nodes = user_path;
+    nodes.extend(resource_path.into_iter().rev());
+    Some(DerivationPath {
+        nodes,
+        edges,
+        operations,
+    })
+}
+
+/// 在已加载用户和资源向上子图的基础上，计算有效操作和推导路径，无法访问时为 None
+///
+/// 有效操作为关联授予的操作中逐一通过判定（含策略类和禁止关系）的部分。存在禁止关系时，
+/// `*` 先展开为资源及其所属属性声明的资源类型支持的全部操作，再去掉被禁止的操作。
+pub fn access_entry(
+    graph: &PolicySubgraph,
+    resource_types: &HashMap<i64, String>,
+    user_id: i64,
+    resource_id: i64,
+) -> Option<AccessEntry> {
+    let user_closure = graph.assigned_closure(user_id);
+    let resource_closure = graph.assigned_closure(resource_id);
+    let types: Vec<&String> = resource_closure
+        .iter()
+        .filter_map(|id| resource_types.get(id))
+        .collect();
+    let denied = graph.prohibited_operations(user_id, resource_id);
+    let granted = |edge: &EdgeRecord| {
+        if denied.is_empty() {
+            edge.operations()
+        } else {
+            edge.operations().expand_all(&types)
+        }
+    };
+    let associations: Vec<&EdgeRecord> = graph
+        .associations()
+        .filter(|edge| {
+            user_closure.contains(&edge.start_id) && resource_closure.contains(&edge.end_id)
+        })
+        .collect();
+    if associations.is_empty() {
+        return None;
+    }
+
+    let candidates = associations
+        .iter()
+        .fold(AccessRightSet::new(), |candidates, edge| {
+            candidates.union(&granted(edge))
+        });
+    let operations: AccessRightSet = candidates
+        .iter()
+        .filter(|right| graph.is_permitted(user_id, resource_id, Some(right.as_str())))
+        .cloned()
+        .collect();
+    if operations.is_empty() {
+        return None;
+    }
+
+    let paths = associations
+        .into_iter()
+        .filter_map(|edge| {
+            let effective = granted(edge).intersection(&operations);
+            if effective.is_empty() {
+                return None;
+            }
+            derivation_path(graph, user_id, resource_id, edge, effective)
+        })
+        .collect();
+    Some(AccessEntry {
+        user_id,
+        resource_id,
+        operations,
+        paths,
+    })
+}
+
+/// 子图中各节点声明的资源类型，用于展开 `*`
+async fn loaded_resource_types(
+    client: &Client,
+    graph: &PolicySubgraph,
+) -> Result<HashMap<i64, String>, Status> {
+    let ids: Vec<i64> = graph.nodes().map(|(id, _)| id).collect();
+    search_resource_types(client, &ids).await
+}
+
+/// 谁能访问资源：可访问该对象（或对象属性）的全部用户及其有效操作
+pub async fn who_can_access(client: &Client, resource_id: i64) -> Result<Vec<AccessEntry>, Status> {
+    expect_node_type(
+        client,
+        resource_id,
+        &[NodeType::Object, NodeType::ObjectAttribute],
+    )
+    .await?;
+    let graph = PolicySubgraph::load(client, &[resource_id]).await?;
+    let object_attributes: Vec<i64> = graph
+        .assigned_closure(resource_id)
+        .into_iter()
+        .filter(|id| graph.node_type(*id) == Some(NodeType::ObjectAttribute))
+        .collect();
+    if object_attributes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let user_attributes: Vec<i64> =
+        fetch_in_edges(client, &object_attributes, &[EdgeType::Association])
+            .await?
+            .iter()
+            .filter(|edge| edge.is_association())
+            .map(|edge| edge.start_id)
+            .collect::<BTreeSet<i64>>()
+            .into_iter()
+            .collect();
+    let users = assigned_descendants(client, &user_attributes, NodeType::User).await?;
+    if users.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut starts = vec![resource_id];
+    starts.extend(users.iter().copied());
+    let graph = PolicySubgraph::load(client, &starts).await?;
+    let resource_types = loaded_resource_types(client, &graph).await?;
+    Ok(users
+        .into_iter()
+        .filter_map(|user_id| access_entry(&graph, &resource_types, user_id, resource_id))
+        .collect())
+}
+
+/// 用户能访问什么：用户可访问的全部对象及其有效操作
+pub async fn what_can_access(client: &Client, user_id: i64) -> Result<Vec<AccessEntry>, Status> {
+    expect_node_type(client, user_id, &[NodeType::User]).await?;
+    let graph = PolicySubgraph::load(client, &[user_id]).await?;
+    let user_closure = graph.assigned_closure(user_id);
+    let object_attributes: Vec<i64> = graph
+        .associations()
+        .filter(|edge| user_closure.contains(&edge.start_id))
+        .map(|edge| edge.end_id)
+        .collect::<BTreeSet<i64>>()
+        .into_iter()
+        .collect();
+    if object_attributes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let objects = assigned_descendants(client, &object_attributes, NodeType::Object).await?;
+    if objects.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut starts = vec![user_id];
+    starts.extend(objects.iter().copied());
+    let graph = PolicySubgraph::load(client, &starts).await?;
+    let resource_types = loaded_resource_types(client, &graph).await?;
+    Ok(objects
+        .into_iter()
+        .filter_map(|resource_id| access_entry(&graph, &resource_types, user_id, resource_id))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    use super::access_entry;
+    use crate::graph::access_right::{AccessRight, AccessRightSet, OperationRegistry};
+    use crate::graph::decision::tests::{assign, edge};
+    use crate::graph::decision::{EdgeRecord, PolicySubgraph};
+    use crate::graph::edge::EdgeType;
+    use crate::graph::{
+        search_target_ids_to_assigned_origin_node_path_cypher, NodeType, ASSOCIATION, PROHIBITION,
+    };
+
+    const U1: (NodeType, i64) = (NodeType::User, 1);
+    const UA2: (NodeType, i64) = (NodeType::UserAttribute, 2);
+    const UA5: (NodeType, i64) = (NodeType::UserAttribute, 5);
+    const O3: (NodeType, i64) = (NodeType::Object, 3);
+    const OA4: (NodeType, i64) = (NodeType::ObjectAttribute, 4);
+    const OA6: (NodeType, i64) = (NodeType::ObjectAttribute, 6);
+    const PC10: (NodeType, i64) = (NodeType::PolicyClass, 10);
+
+    /// u1 → ua2, ua5 → pc10 ← oa4 ← o3，pc10 ← oa6；ua2 -[read, write]-> oa4，ua5 -[delete]-> oa4
+    fn graph(prohibitions: &[EdgeRecord]) -> PolicySubgraph {
+        let mut graph = PolicySubgraph::default();
+        for edge in [
+            assign(U1, UA2),
+            assign(U1, UA5),
+            assign(UA2, PC10),
+            assign(UA5, PC10),
+            assign(O3, OA4),
+            assign(OA4, PC10),
+            assign(OA6, PC10),
+            edge(
+                UA2,
+                ASSOCIATION,
+                OA4,
+                json!({"operations": ["read", "write"]}),
+            ),
+            edge(UA5, ASSOCIATION, OA4, json!({"operations": ["delete"]})),
+        ] {
+            graph.insert_edge(edge);
+        }
+        for prohibition in prohibitions {
+            graph.insert_edge(prohibition.clone());
+        }
+        graph
+    }
+
+    fn deny(
+        subject: (NodeType, i64),
+        container: (NodeType, i64),
+        operations: &[&str],
+        complement: bool,
+    ) -> EdgeRecord {
+        let properties = json!({
+            "name": "deny",
+            "operations": operations,
+            "intersection": false,
+            "complement": complement,
+        });
+        edge(subject, PROHIBITION, container, properties)
+    }
+
+    fn rights(operations: &[&str]) -> AccessRightSet {
+        operations.iter().map(|op| AccessRight::from(*op)).collect()
+    }
+
+    #[test]
+    fn paths_without_prohibitions() {
+        let entry = access_entry(&graph(&[]), &HashMap::new(), 1, 3).unwrap();
+        assert_eq!(entry.operations, rights(&["read", "write", "delete"]));
+        assert_eq!(entry.paths.len(), 2);
+        for path in entry.paths.iter() {
+            assert_eq!(path.nodes[0], 1);
+            assert_eq!(path.nodes[2..], [4, 3]);
+            assert_eq!(
+                path.edges,
+                [
+                    EdgeType::Assignment,
+                    EdgeType::Association,
+                    EdgeType::Assignment
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn prohibition_narrows_path_operations() {
+        let entry = access_entry(
+            &graph(&[deny(UA2, OA4, &["write"], false)]),
+            &HashMap::new(),
+            1,
+            3,
+        )
+        .unwrap();
+        assert_eq!(entry.operations, rights(&["read", "delete"]));
+        let path = entry.paths.iter().find(|p| p.nodes[1] == 2).unwrap();
+        assert_eq!(path.nodes, [1, 2, 4, 3]);
+        assert_eq!(path.operations, rights(&["read"]));
+    }
+
+    #[test]
+    fn fully_prohibited_association_has_no_path() {
+        let entry = access_entry(
+            &graph(&[deny(U1, OA4, &["delete"], false)]),
+            &HashMap::new(),
+            1,
+            3,
+        )
+        .unwrap();
+        assert_eq!(entry.operations, rights(&["read", "write"]));
+        assert_eq!(entry.paths.len(), 1);
+        assert_eq!(entry.paths[0].nodes, [1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn complement_prohibition_covers_resources_outside_container() {
+        let outside = graph(&[deny(UA5, OA6, &["read", "write", "delete"], true)]);
+        assert!(access_entry(&outside, &HashMap::new(), 1, 3).is_none());
+
+        let inside = graph(&[deny(UA5, OA4, &["read", "write", "delete"], true)]);
+        let entry = access_entry(&inside, &HashMap::new(), 1, 3).unwrap();
+        assert_eq!(entry.operations, rights(&["read", "write", "delete"]));
+        assert_eq!(entry.paths.len(), 2);
+    }
+
+    #[test]
+    fn prohibition_is_subtracted_from_expanded_wildcard() {
+        OperationRegistry::load_json(r#"{"test-review": ["share"]}"#).unwrap();
+        let mut graph = graph(&[]);
+        graph.insert_edge(edge(UA2, ASSOCIATION, OA4, json!({"operations": ["*"]})));
+
+        let entry = access_entry(&graph, &HashMap::new(), 1, 3).unwrap();
+        assert_eq!(entry.operations, rights(&["*", "read", "write", "delete"]));
+
+        graph.insert_edge(deny(U1, OA4, &["write"], false));
+        let resource_types = HashMap::from([(4, "test-review".to_owned())]);
+        let entry = access_entry(&graph, &resource_types, 1, 3).unwrap();
+        assert_eq!(entry.operations, rights(&["read", "delete", "share"]));
+        let path = entry
+            .paths
+            .iter()
+            .find(|p| p.operations.grants("share"))
+            .unwrap();
+        assert_eq!(path.nodes, [1, 2, 4, 3]);
+
+        let entry = access_entry(&graph, &HashMap::new(), 1, 3).unwrap();
+        assert_eq!(entry.operations, rights(&["read", "delete"]));
+    }
+
+    #[test]
+    fn descendants_follow_assignment_paths() {
+        let query =
+            search_target_ids_to_assigned_origin_node_path_cypher(NodeType::User, vec![2, 5]);
+        let (cypher, params) = query.build().unwrap();
+        assert_eq!(
+            cypher,
+            "MATCH (origin: User)-[:Assignment*]->(target) WHERE id(target) IN [2, 5] RETURN DISTINCT id(origin)"
+        );
+        assert!(params.is_none());
+    }
+}
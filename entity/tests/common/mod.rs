@@ -0,0 +1,24 @@
+use entity::graph::ensure_graph;
+use pool::age::AgeClientExtend;
+use tokio_postgres::{Client, NoTls};
+
+/// 以 `DATABASE_URL` 建立连接，测试需要本地的 Postgres（图相关的测试还需要 AGE 扩展）
+pub async fn connect() -> Client {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL not set");
+    let (client, connection) = tokio_postgres::connect(&database_url, NoTls)
+        .await
+        .expect("connect to DATABASE_URL failed");
+    tokio::spawn(connection);
+    client
+}
+
+/// 加载 AGE 并确保 ngac 图存在
+#[allow(dead_code)]
+pub async fn connect_graph() -> Client {
+    let client = connect().await;
+    Client::connect_age_extend(&client)
+        .await
+        .expect("load age failed");
+    ensure_graph(&client).await.expect("create graph failed");
+    client
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use entity::graph::access_right::{AccessRight, AccessRightSet};
+use entity::graph::decision::check_permission;
+use entity::graph::{
+    assignment_tx, create_node, in_transaction, search_node, Assignment, NodeType, NodeTypeObject,
+    Transaction,
+};
+use pilota::AHashMap;
+use tokio_postgres::Client;
+use volo_grpc::Status;
+
+/// 节点名加上测试名前缀，并发运行的测试不会争用同名节点的唯一索引
+async fn node(client: &Client, prefix: &str, node_type: NodeType, name: &str) -> i64 {
+    let name = format!("{}-{}", prefix, name);
+    let object = NodeTypeObject::new(node_type, name.clone(), AHashMap::default());
+    if let Some(e) = create_node(client, object).await {
+        panic!("create {} failed: {:?}", name, e);
+    }
+    search_node(client, node_type, Some(&name), None, AHashMap::default())
+        .await
+        .unwrap()
+        .id()
+}
+
+async fn assign(tx: &Transaction<'_>, edge: Assignment) {
+    if let Err(e) = assignment_tx(tx, edge).await {
+        panic!("assignment failed: {:?}", e);
+    }
+}
+
+fn operations(operations: &[&str]) -> AccessRightSet {
+    operations.iter().map(|op| AccessRight::from(*op)).collect()
+}
+
+async fn permitted(client: &Client, user_id: i64, resource_id: i64, operation: &str) -> bool {
+    check_permission(client, user_id, resource_id, Some(operation))
+        .await
+        .unwrap()
+}
+
+/// 在事务中搭建策略并断言，结束后回滚，不在图中留下数据
+async fn rolled_back(client: &Client, test: impl AsyncFnOnce(&Transaction<'_>)) {
+    let result = in_transaction(client, async |tx| {
+        test(tx).await;
+        Err::<(), _>(Status::aborted("rollback"))
+    })
+    .await;
+    assert_eq!(result.unwrap_err().message(), "rollback");
+}
+
+/// 需要本地 Postgres + AGE：`DATABASE_URL=... cargo test -p entity -- --ignored`
+///
+/// u → ua1 → pc1 ← oa1 ← o，u → ua2 → pc2 ← oa2 ← o；ua1 -[read, write]-> oa1，ua2 -[read]-> oa2
+#[tokio::test]
+#[ignore]
+async fn every_shared_policy_class_must_grant() {
+    let client = common::connect_graph().await;
+    let client = &client;
+    rolled_back(client, async |tx| {
+        let p = "every_shared_policy_class_must_grant";
+        let pc1 = node(tx, p, NodeType::PolicyClass, "pc1").await;
+        let pc2 = node(tx, p, NodeType::PolicyClass, "pc2").await;
+        let ua1 = node(tx, p, NodeType::UserAttribute, "ua1").await;
+        let ua2 = node(tx, p, NodeType::UserAttribute, "ua2").await;
+        let oa1 = node(tx, p, NodeType::ObjectAttribute, "oa1").await;
+        let oa2 = node(tx, p, NodeType::ObjectAttribute, "oa2").await;
+        let u = node(tx, p, NodeType::User, "u").await;
+        let o = node(tx, p, NodeType::Object, "o").await;
+        let only_pc1 = node(tx, p, NodeType::Object, "only_pc1").await;
+        for edge in [
+            Assignment::UA2PC((ua1, pc1)),
+            Assignment::UA2PC((ua2, pc2)),
+            Assignment::OA2PC((oa1, pc1)),
+            Assignment::OA2PC((oa2, pc2)),
+            Assignment::U2UA((u, ua1)),
+            Assignment::U2UA((u, ua2)),
+            Assignment::O2OA((o, oa1)),
+            Assignment::O2OA((o, oa2)),
+            Assignment::O2OA((only_pc1, oa1)),
+            Assignment::UA2OA((ua1, oa1), operations(&["read", "write"])),
+            Assignment::UA2OA((ua2, oa2), operations(&["read"])),
+        ] {
+            assign(tx, edge).await;
+        }
+
+        assert!(permitted(tx, u, o, "read").await);
+        // pc2 没有授予 write
+        assert!(!permitted(tx, u, o, "write").await);
+        assert!(check_permission(tx, u, o, None).await.unwrap());
+        // 只属于 pc1 的对象只需要 pc1 授予
+        assert!(permitted(tx, u, only_pc1, "write").await);
+    })
+    .await;
+}
+
+/// u → ua_child → ua → pc ← oa ← oa_child ← o，ua -[read]-> oa；另有只属于 pc_other 的对象
+#[tokio::test]
+#[ignore]
+async fn containment_is_inherited_through_attributes() {
+    let client = common::connect_graph().await;
+    let client = &client;
+    rolled_back(client, async |tx| {
+        let p = "containment_is_inherited_through_attributes";
+        let pc = node(tx, p, NodeType::PolicyClass, "pc").await;
+        let pc_other = node(tx, p, NodeType::PolicyClass, "pc_other").await;
+        let ua = node(tx, p, NodeType::UserAttribute, "ua").await;
+        let ua_child = node(tx, p, NodeType::UserAttribute, "ua_child").await;
+        let oa = node(tx, p, NodeType::ObjectAttribute, "oa").await;
+        let oa_child = node(tx, p, NodeType::ObjectAttribute, "oa_child").await;
+        let oa_other = node(tx, p, NodeType::ObjectAttribute, "oa_other").await;
+        let u = node(tx, p, NodeType::User, "u").await;
+        let o = node(tx, p, NodeType::Object, "o").await;
+        let o_other = node(tx, p, NodeType::Object, "o_other").await;
+        for edge in [
+            Assignment::UA2PC((ua, pc)),
+            Assignment::UA2UA((ua_child, ua)),
+            Assignment::OA2PC((oa, pc)),
+            Assignment::OA2OA((oa_child, oa)),
+            Assignment::OA2PC((oa_other, pc_other)),
+            Assignment::U2UA((u, ua_child)),
+            Assignment::O2OA((o, oa_child)),
+            Assignment::O2OA((o_other, oa_other)),
+            Assignment::UA2OA((ua, oa), operations(&["read"])),
+        ] {
+            assign(tx, edge).await;
+        }
+
+        assert!(permitted(tx, u, o, "read").await);
+        assert!(permitted(tx, u, oa_child, "read").await);
+        assert!(!permitted(tx, u, o, "write").await);
+        // 没有共同的策略类
+        assert!(!permitted(tx, u, o_other, "read").await);
+        assert!(!check_permission(tx, u, o_other, None).await.unwrap());
+    })
+    .await;
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use entity::graph::{in_transaction, lock_graph, GRAPH_NAME};
+use volo_grpc::Status;
+
+async fn insert(client: &tokio_postgres::Client, value: i32) -> Result<(), Status> {
+    client
+        .execute("INSERT INTO nested_transaction VALUES ($1)", &[&value])
+        .await
+        .map_err(|e| Status::from_error(Box::new(e)))?;
+    Ok(())
+}
+
+async fn values(client: &tokio_postgres::Client) -> Vec<i32> {
+    client
+        .query("SELECT value FROM nested_transaction ORDER BY value", &[])
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| row.get(0))
+        .collect()
+}
+
+/// 需要本地 Postgres：`DATABASE_URL=... cargo test -p entity -- --ignored`
+#[tokio::test]
+#[ignore]
+async fn savepoint_rolls_back_only_its_own_statements() {
+    let client = common::connect().await;
+    client
+        .batch_execute("CREATE TEMP TABLE nested_transaction (value int)")
+        .await
+        .unwrap();
+
+    in_transaction(&client, async |tx| {
+        insert(tx, 1).await?;
+        let savepoint = tx.savepoint().await?;
+        let inner = async {
+            insert(&savepoint, 2).await?;
+            Err::<(), _>(Status::aborted("inner failed"))
+        }
+        .await;
+        assert!(savepoint.finish(inner).await.is_err());
+        insert(tx, 3).await
+    })
+    .await
+    .unwrap();
+    assert_eq!(values(&client).await, vec![1, 3]);
+
+    let outer = in_transaction(&client, async |tx| {
+        let savepoint = tx.savepoint().await?;
+        insert(&savepoint, 4).await?;
+        savepoint.commit().await?;
+        Err::<(), _>(Status::aborted("outer failed"))
+    })
+    .await;
+    assert!(outer.is_err());
+    assert_eq!(values(&client).await, vec![1, 3]);
+}
+
+/// future 在 BEGIN 与 COMMIT 之间被取消时，连接上的事务被回滚而不是留给下一个使用者
+#[tokio::test]
+#[ignore]
+async fn cancelled_transaction_is_rolled_back() {
+    let client = common::connect().await;
+    client
+        .batch_execute("CREATE TEMP TABLE nested_transaction (value int)")
+        .await
+        .unwrap();
+
+    let inserted = tokio::sync::Notify::new();
+    let cancelled = in_transaction(&client, async |tx| {
+        insert(tx, 1).await?;
+        inserted.notify_one();
+        std::future::pending::<Result<(), Status>>().await
+    });
+    tokio::select! {
+        biased;
+        _ = cancelled => unreachable!(),
+        _ = inserted.notified() => {}
+    }
+
+    // 仍在事务中时同一连接能看到未提交的 1
+    insert(&client, 2).await.unwrap();
+    assert_eq!(values(&client).await, vec![2]);
+}
+
+#[tokio::test]
+#[ignore]
+async fn graph_lock_is_held_until_commit() {
+    let client = common::connect().await;
+    let other = common::connect().await;
+    let try_lock = "SELECT pg_try_advisory_xact_lock(hashtext($1))";
+
+    in_transaction(&client, async |tx| {
+        lock_graph(tx).await?;
+        let locked: bool = other
+            .query_one(try_lock, &[&GRAPH_NAME])
+            .await
+            .unwrap()
+            .get(0);
+        assert!(!locked);
+        Ok(())
+    })
+    .await
+    .unwrap();
+    let locked: bool = other
+        .query_one(try_lock, &[&GRAPH_NAME])
+        .await
+        .unwrap()
+        .get(0);
+    assert!(locked);
+}
@@ -1,14 +1,121 @@
 use super::snow_worker::SnowWorker;
+use super::snow_worker_m2::SnowWorkerM2;
+use std::sync::Arc;
 
-// static mut instance2: Option<Arc<Mutex<SnowWorkerM1>>> = None;
+use super::{IGenIdObserver, ISnowWorker, IdGenError, IdGeneratorOptions, IdParts};
+use crate::lease::LeaseHandle;
 
-#[derive(Copy, Clone)]
 pub struct DefaultIdGenerator {
-    pub worker: SnowWorker,
+    pub worker: Box<dyn ISnowWorker>,
+    options: IdGeneratorOptions,
+    lease: Option<LeaseHandle>,
+    observers: Vec<Arc<dyn IGenIdObserver>>,
 }
 
 impl DefaultIdGenerator {
     pub fn default() -> Self {
-        Self { worker: SnowWorker::default() }
+        Self::new(IdGeneratorOptions::new(1))
+    }
+
+    /// 配置不合法时 panic，需要处理错误请使用 [`DefaultIdGenerator::try_new`]
+    pub fn new(options: IdGeneratorOptions) -> Self {
+        Self::try_new(options).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_new(options: IdGeneratorOptions) -> Result<Self, IdGenError> {
+        Ok(Self {
+            worker: Self::build_worker(options.clone())?,
+            options,
+            lease: None,
+            observers: Vec::new(),
+        })
+    }
+
+    /// 根据 Method 选择雪花算法实现：1-漂移算法，2-传统算法
+    fn build_worker(options: IdGeneratorOptions) -> Result<Box<dyn ISnowWorker>, IdGenError> {
+        match options.method {
+            1 => Ok(Box::new(SnowWorker::try_new(options)?)),
+            2 => Ok(Box::new(SnowWorkerM2::try_new(options)?)),
+            method => Err(IdGenError::Method(method)),
+        }
+    }
+
+    /// 算法不变时沿用当前 worker 的状态，切换算法时重建 worker 并从上次发号的时间戳之后继续
+    pub fn set_options(&mut self, options: IdGeneratorOptions) -> Result<(), IdGenError> {
+        if options.method == self.options.method {
+            self.worker.set_options(options.clone())?;
+        } else {
+            let mut worker = Self::build_worker(options.clone())?;
+            if let Some(timestamp) = self.worker.last_timestamp() {
+                worker.resume_after(timestamp);
+            }
+            for observer in self.observers.iter() {
+                worker.add_observer(observer.clone());
+            }
+            self.worker = worker;
+        }
+        self.options = options;
+        Ok(())
+    }
+
+    /// 观察者在切换雪花算法重建 worker 后依然生效
+    pub fn add_observer(&mut self, observer: Arc<dyn IGenIdObserver>) {
+        self.worker.add_observer(observer.clone());
+        self.observers.push(observer);
+    }
+
+    pub fn options(&self) -> &IdGeneratorOptions {
+        &self.options
+    }
+
+    pub fn decode(&self, id: i64) -> IdParts {
+        self.options.decode(id)
+    }
+
+    /// 绑定机器码租约后，租约失效或机器码与租约不一致时拒绝发号
+    pub fn set_lease(&mut self, lease: Option<LeaseHandle>) {
+        self.lease = lease;
+    }
+
+    fn check_lease(&self) -> Result<(), IdGenError> {
+        if let Some(lease) = &self.lease {
+            if !lease.is_valid() || lease.worker_id() != self.options.worker_id {
+                return Err(IdGenError::LeaseLost(lease.worker_id()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn try_next_id(&mut self) -> Result<i64, IdGenError> {
+        self.check_lease()?;
+        Ok(self.worker.next_id())
+    }
+
+    /// 一次性分配 count 个ID，期间不释放 worker，同一毫秒内的序列数连续
+    pub fn try_next_ids(&mut self, count: usize) -> Result<Vec<i64>, IdGenError> {
+        self.check_lease()?;
+        Ok((0..count).map(|_| self.worker.next_id()).collect())
+    }
+
+    /// 不阻塞地向 ids 补足至 count 个ID，需要等待系统时间推进时返回 false
+    pub fn fill_ids(&mut self, ids: &mut Vec<i64>, count: usize) -> Result<bool, IdGenError> {
+        self.check_lease()?;
+        while ids.len() < count {
+            match self.worker.poll_next_id() {
+                Some(id) => ids.push(id),
+                None => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    /// 租约失效时 panic，需要处理错误请使用 [`DefaultIdGenerator::try_next_id`]
+    pub fn next_id(&mut self) -> i64 {
+        self.try_next_id().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 租约失效时 panic，需要处理错误请使用 [`DefaultIdGenerator::try_next_ids`]
+    pub fn next_ids(&mut self, count: usize) -> Vec<i64> {
+        self.try_next_ids(count).unwrap_or_else(|e| panic!("{}", e))
     }
 }
@@ -0,0 +1,84 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{OverCostActionArg, OverCostActionType};
+
+/// 漂移、时间回拨事件的观察者
+///
+/// 回调在生成器的锁内同步执行，实现需要足够轻量，耗时操作请转交给其他线程处理。
+pub trait IGenIdObserver: Send + Sync {
+    fn on_gen_id_action(&self, arg: &OverCostActionArg);
+}
+
+/// 内置计数器，统计各类事件发生次数
+#[derive(Debug, Default)]
+pub struct GenIdActionCounter {
+    begin_over_cost: AtomicU64,
+    end_over_cost: AtomicU64,
+    begin_turn_back: AtomicU64,
+    end_turn_back: AtomicU64,
+}
+
+impl GenIdActionCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin_over_cost(&self) -> u64 {
+        self.begin_over_cost.load(Ordering::Relaxed)
+    }
+
+    pub fn end_over_cost(&self) -> u64 {
+        self.end_over_cost.load(Ordering::Relaxed)
+    }
+
+    pub fn begin_turn_back(&self) -> u64 {
+        self.begin_turn_back.load(Ordering::Relaxed)
+    }
+
+    pub fn end_turn_back(&self) -> u64 {
+        self.end_turn_back.load(Ordering::Relaxed)
+    }
+}
+
+impl IGenIdObserver for GenIdActionCounter {
+    fn on_gen_id_action(&self, arg: &OverCostActionArg) {
+        let counter = match arg.action_type {
+            OverCostActionType::BeginOverCost => &self.begin_over_cost,
+            OverCostActionType::EndOverCost => &self.end_over_cost,
+            OverCostActionType::BeginTurnBack => &self.begin_turn_back,
+            OverCostActionType::EndTurnBack => &self.end_turn_back,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 内置 `tracing` 输出：漂移记为 info，时间回拨记为 warn
+#[derive(Debug, Default)]
+pub struct TracingGenIdObserver;
+
+impl IGenIdObserver for TracingGenIdObserver {
+    fn on_gen_id_action(&self, arg: &OverCostActionArg) {
+        match arg.action_type {
+            OverCostActionType::BeginOverCost | OverCostActionType::EndOverCost => {
+                tracing::info!(
+                    action = ?arg.action_type,
+                    worker_id = arg.worker_id,
+                    time_tick = arg.time_tick,
+                    term_index = arg.term_index,
+                    over_cost_count = arg.over_cost_count_in_one_term,
+                    gen_count = arg.gen_count_in_one_term,
+                    "snowflake over cost"
+                );
+            }
+            OverCostActionType::BeginTurnBack | OverCostActionType::EndTurnBack => {
+                tracing::warn!(
+                    action = ?arg.action_type,
+                    worker_id = arg.worker_id,
+                    time_tick = arg.time_tick,
+                    turn_back_index = arg.term_index,
+                    "snowflake clock turn back"
+                );
+            }
+        }
+    }
+}
@@ -1,3 +1,31 @@
-pub trait ISnowWorker {
-    fn next_id(&self) -> i64;
-}
\ No newline at end of file
+use std::sync::Arc;
+use std::thread::sleep;
+
+use super::{IGenIdObserver, IdGenError, IdGeneratorOptions};
+
+pub trait ISnowWorker: Send {
+    fn set_options(&mut self, options: IdGeneratorOptions) -> Result<(), IdGenError>;
+
+    /// 最后一次发号使用的时间戳（毫秒），尚未发号时为 None
+    fn last_timestamp(&self) -> Option<i64>;
+
+    /// 从该时间戳之后继续发号，用于替换 worker 时衔接上一个 worker 的状态
+    fn resume_after(&mut self, timestamp: i64);
+
+    /// 不阻塞地生成下一个ID，需要等待系统时间推进（序列数耗尽、时间回拨等）时返回 None
+    fn poll_next_id(&mut self) -> Option<i64>;
+
+    /// 注册漂移、时间回拨事件的观察者
+    fn add_observer(&mut self, observer: Arc<dyn IGenIdObserver>);
+
+    /// 阻塞直到生成下一个ID
+    fn next_id(&mut self) -> i64 {
+        loop {
+            if let Some(id) = self.poll_next_id() {
+                return id;
+            }
+            // 暂停1ms
+            sleep(std::time::Duration::from_millis(1));
+        }
+    }
+}
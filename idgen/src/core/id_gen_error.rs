@@ -0,0 +1,46 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IdGenError {
+    #[error("Method error. (expected 1 or 2, got {0})")]
+    Method(u8),
+
+    #[error("BaseTime error. (range: [{min}, {max}], got {value})")]
+    BaseTime { value: i64, min: i64, max: i64 },
+
+    #[error("WorkerIdBitLength error. (range: [1, 21], got {0})")]
+    WorkerIdBitLength(u8),
+
+    #[error("SeqBitLength error. (range: [2, 21], got {0})")]
+    SeqBitLength(u8),
+
+    #[error("WorkerIdBitLength + SeqBitLength must not exceed 22. (got {worker_id_bit_length} + {seq_bit_length})")]
+    BitLengthOverflow {
+        worker_id_bit_length: u8,
+        seq_bit_length: u8,
+    },
+
+    #[error("WorkerId error. (range: [0, {max}], got {value})")]
+    WorkerId { value: u32, max: u32 },
+
+    #[error("MaxSeqNumber error. (range: [1, {max}], got {value})")]
+    MaxSeqNumber { value: u32, max: u32 },
+
+    #[error("MinSeqNumber error. (range: [5, {max}], got {value})")]
+    MinSeqNumber { value: u32, max: u32 },
+
+    #[error("TopOverCostCount error. (range: [0, 10000], got {0})")]
+    TopOverCostCount(u32),
+
+    #[error("No free worker id in range [0, {0}].")]
+    NoFreeWorkerId(u32),
+
+    #[error("Worker id {0} lease lost, refusing to generate ids.")]
+    LeaseLost(u32),
+
+    #[error("Id generator \"{0}\" is not registered.")]
+    UnknownGenerator(String),
+
+    #[error("Redis error: {0}")]
+    Redis(String),
+}
@@ -1,3 +1,14 @@
+use chrono::Utc;
+
+use super::{IdGenError, IdParts};
+
+/// 未设置 BaseTime（0）时使用的默认基础时间
+pub const DEFAULT_BASE_TIME: i64 = 1582136402000;
+
+/// BaseTime 允许的最小值（1990-01-01）
+pub const MIN_BASE_TIME: i64 = 631123200000;
+
+#[derive(Debug, Clone)]
 pub struct IdGeneratorOptions {
     /// 雪花计算方法,（1-漂移算法|2-传统算法），默认1
     pub method: u8,
@@ -29,7 +40,7 @@ impl IdGeneratorOptions {
         return IdGeneratorOptions {
             method: 1,
             worker_id,
-            base_time: 1582136402000,
+            base_time: DEFAULT_BASE_TIME,
             worker_id_bit_length: 6,
             seq_bit_length: 6,
             max_seq_number: 0,
@@ -37,4 +48,118 @@ impl IdGeneratorOptions {
             top_over_cost_count: 2000,
         };
     }
-}
\ No newline at end of file
+
+    /// 生效的基础时间，0 表示使用默认值
+    pub fn effective_base_time(&self) -> i64 {
+        if self.base_time == 0 {
+            DEFAULT_BASE_TIME
+        } else {
+            self.base_time
+        }
+    }
+
+    /// 生效的最大序列数，0 表示取 2^SeqBitLength-1
+    pub fn effective_max_seq_number(&self) -> u32 {
+        if self.max_seq_number == 0 {
+            (1 << self.seq_bit_length) - 1
+        } else {
+            self.max_seq_number
+        }
+    }
+
+    /// 校验全部配置项，返回第一个不合法的配置
+    pub fn validate(&self) -> Result<(), IdGenError> {
+        // 1.Method
+        if self.method != 1 && self.method != 2 {
+            return Err(IdGenError::Method(self.method));
+        }
+
+        // 2.BaseTime
+        let now = Utc::now().timestamp_millis();
+        if self.base_time != 0 && (self.base_time < MIN_BASE_TIME || self.base_time > now) {
+            return Err(IdGenError::BaseTime {
+                value: self.base_time,
+                min: MIN_BASE_TIME,
+                max: now,
+            });
+        }
+
+        // 3.WorkerIdBitLength / SeqBitLength
+        if self.worker_id_bit_length < 1 || self.worker_id_bit_length > 21 {
+            return Err(IdGenError::WorkerIdBitLength(self.worker_id_bit_length));
+        }
+        if self.seq_bit_length < 2 || self.seq_bit_length > 21 {
+            return Err(IdGenError::SeqBitLength(self.seq_bit_length));
+        }
+        if self.worker_id_bit_length + self.seq_bit_length > 22 {
+            return Err(IdGenError::BitLengthOverflow {
+                worker_id_bit_length: self.worker_id_bit_length,
+                seq_bit_length: self.seq_bit_length,
+            });
+        }
+
+        // 4.WorkerId
+        let max_worker_id_number = (1 << self.worker_id_bit_length) - 1;
+        if self.worker_id > max_worker_id_number {
+            return Err(IdGenError::WorkerId {
+                value: self.worker_id,
+                max: max_worker_id_number,
+            });
+        }
+
+        // 5.MaxSeqNumber
+        let max_seq_number = (1 << self.seq_bit_length) - 1;
+        if self.max_seq_number > max_seq_number {
+            return Err(IdGenError::MaxSeqNumber {
+                value: self.max_seq_number,
+                max: max_seq_number,
+            });
+        }
+
+        // 6.MinSeqNumber
+        let max_seq_number = self.effective_max_seq_number();
+        if self.min_seq_number < 5 || self.min_seq_number > max_seq_number {
+            return Err(IdGenError::MinSeqNumber {
+                value: self.min_seq_number,
+                max: max_seq_number,
+            });
+        }
+
+        // 7.TopOverCostCount
+        if self.top_over_cost_count > 10000 {
+            return Err(IdGenError::TopOverCostCount(self.top_over_cost_count));
+        }
+
+        Ok(())
+    }
+
+    fn timestamp_shift(&self) -> u8 {
+        self.worker_id_bit_length + self.seq_bit_length
+    }
+
+    /// 按当前配置的位长拆解ID，配置需与生成该ID时一致
+    pub fn decode(&self, id: i64) -> IdParts {
+        let time_tick = id >> self.timestamp_shift();
+        let worker_id =
+            ((id >> self.seq_bit_length) & ((1 << self.worker_id_bit_length) - 1)) as u32;
+        let seq_number = (id & ((1 << self.seq_bit_length) - 1)) as u32;
+        IdParts {
+            time_tick,
+            timestamp: time_tick + self.effective_base_time(),
+            worker_id,
+            seq_number,
+            // 每毫秒序列数的前5位是预留位，0用于手工新值，1-4是时间回拨次序
+            is_turn_back: (1..=4).contains(&seq_number),
+        }
+    }
+
+    /// 指定 Unix 时间戳（ms单位）内可能生成的最小ID，与 [`max_id_at`](Self::max_id_at) 配合用于按时间范围查询
+    pub fn min_id_at(&self, timestamp: i64) -> i64 {
+        (timestamp - self.effective_base_time()) << self.timestamp_shift()
+    }
+
+    /// 指定 Unix 时间戳（ms单位）内可能生成的最大ID（含所有机器码与序列数）
+    pub fn max_id_at(&self, timestamp: i64) -> i64 {
+        self.min_id_at(timestamp + 1) - 1
+    }
+}
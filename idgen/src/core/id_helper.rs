@@ -1,30 +1,194 @@
-use std::sync::OnceLock;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock};
+use std::time::Duration;
 
 use super::DefaultIdGenerator;
+use super::IGenIdObserver;
+use super::IdGenError;
 use super::IdGeneratorOptions;
+use super::IdParts;
+use crate::lease::{LeaseHandle, WorkerIdLease};
 
 pub struct IdHelper;
 
-static ID_GEN_INSTANCE: OnceLock<DefaultIdGenerator> = OnceLock::new();
+/// 进程全局生成器，`SnowWorker` 状态在锁内修改，保证多线程下不会重复发号
+static ID_GEN_INSTANCE: OnceLock<Mutex<DefaultIdGenerator>> = OnceLock::new();
+
+/// 具名生成器，各自持有独立的配置与 `SnowWorker` 状态
+static ID_GEN_REGISTRY: OnceLock<RwLock<HashMap<String, Arc<Mutex<DefaultIdGenerator>>>>> =
+    OnceLock::new();
 
 impl IdHelper {
-    fn id_gen_instance() -> DefaultIdGenerator {
-        *ID_GEN_INSTANCE.get_or_init(|| DefaultIdGenerator::default())
+    fn lock(instance: &Mutex<DefaultIdGenerator>) -> MutexGuard<'_, DefaultIdGenerator> {
+        // 持锁线程 panic 后 worker 状态仍然可用，继续发号而不是让所有调用方一起 panic
+        instance.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn id_gen_instance() -> MutexGuard<'static, DefaultIdGenerator> {
+        IdHelper::lock(ID_GEN_INSTANCE.get_or_init(|| Mutex::new(DefaultIdGenerator::default())))
+    }
+
+    fn configure(
+        options: IdGeneratorOptions,
+        lease: Option<LeaseHandle>,
+    ) -> Result<(), IdGenError> {
+        // 先校验，保证下面首次初始化时不会因配置错误 panic
+        options.validate()?;
+
+        // 首次使用时直接以传入配置初始化，避免先构造默认 worker 再重新配置
+        let mut options = Some(options);
+        let instance = ID_GEN_INSTANCE
+            .get_or_init(|| Mutex::new(DefaultIdGenerator::new(options.take().unwrap())));
+        let mut idgen = IdHelper::lock(instance);
+        if let Some(options) = options {
+            idgen.set_options(options)?;
+        }
+        idgen.set_lease(lease);
+        Ok(())
     }
 
-    pub fn set_id_generator(options: IdGeneratorOptions) {
-        let mut idgen = IdHelper::id_gen_instance();
-        idgen.worker.set_options(options);
+    pub fn set_id_generator(options: IdGeneratorOptions) -> Result<(), IdGenError> {
+        IdHelper::configure(options, None)
     }
 
-    pub fn set_worker_id(worker_id: u32) {
-        let mut idgen = IdHelper::id_gen_instance();
+    /// 使用租约申请到的机器码配置全局生成器，租约丢失后 [`IdHelper::try_next_id`] 返回错误
+    pub fn set_id_generator_with_lease(
+        mut options: IdGeneratorOptions,
+        lease: &WorkerIdLease,
+    ) -> Result<(), IdGenError> {
+        options.worker_id = lease.worker_id();
+        IdHelper::configure(options, Some(lease.handle()))
+    }
+
+    pub fn set_worker_id(worker_id: u32) -> Result<(), IdGenError> {
         let options = IdGeneratorOptions::new(worker_id);
-        idgen.worker.set_options(options);
+        IdHelper::set_id_generator(options)
+    }
+
+    pub fn add_observer(observer: Arc<dyn IGenIdObserver>) {
+        IdHelper::id_gen_instance().add_observer(observer);
+    }
+
+    pub fn try_next_id() -> Result<i64, IdGenError> {
+        IdHelper::id_gen_instance().try_next_id()
     }
 
     pub fn next_id() -> i64 {
-        let mut idgen = IdHelper::id_gen_instance();
-        idgen.worker.next_id()
+        IdHelper::id_gen_instance().next_id()
+    }
+
+    pub fn try_next_ids(count: usize) -> Result<Vec<i64>, IdGenError> {
+        IdHelper::id_gen_instance().try_next_ids(count)
+    }
+
+    pub fn next_ids(count: usize) -> Vec<i64> {
+        IdHelper::id_gen_instance().next_ids(count)
+    }
+
+    /// 需要等待系统时间推进时释放锁并通过 `tokio::time::sleep` 让出执行权，不阻塞运行时线程
+    async fn fill_ids_async(
+        instance: &Mutex<DefaultIdGenerator>,
+        count: usize,
+    ) -> Result<Vec<i64>, IdGenError> {
+        let mut ids = Vec::with_capacity(count);
+        loop {
+            if IdHelper::lock(instance).fill_ids(&mut ids, count)? {
+                return Ok(ids);
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    pub async fn next_ids_async(count: usize) -> Result<Vec<i64>, IdGenError> {
+        let instance = ID_GEN_INSTANCE.get_or_init(|| Mutex::new(DefaultIdGenerator::default()));
+        IdHelper::fill_ids_async(instance, count).await
+    }
+
+    pub async fn next_id_async() -> Result<i64, IdGenError> {
+        Ok(IdHelper::next_ids_async(1).await?[0])
+    }
+
+    /// 按全局生成器当前的配置拆解ID
+    pub fn decode(id: i64) -> IdParts {
+        IdHelper::id_gen_instance().decode(id)
+    }
+
+    pub fn min_id_at(timestamp: i64) -> i64 {
+        IdHelper::id_gen_instance().options().min_id_at(timestamp)
+    }
+
+    pub fn max_id_at(timestamp: i64) -> i64 {
+        IdHelper::id_gen_instance().options().max_id_at(timestamp)
+    }
+
+    fn registry() -> &'static RwLock<HashMap<String, Arc<Mutex<DefaultIdGenerator>>>> {
+        ID_GEN_REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+    }
+
+    fn named_instance(name: &str) -> Result<Arc<Mutex<DefaultIdGenerator>>, IdGenError> {
+        IdHelper::registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+            .cloned()
+            .ok_or_else(|| IdGenError::UnknownGenerator(name.to_owned()))
+    }
+
+    /// 注册具名生成器，已存在时按新配置重新设置（保留原 worker 状态）
+    ///
+    /// 不同生成器若使用相同的 WorkerId 与位长，生成的ID可能重复，只应用于互不相关的ID空间。
+    pub fn register(name: &str, options: IdGeneratorOptions) -> Result<(), IdGenError> {
+        options.validate()?;
+        let mut registry = IdHelper::registry()
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        match registry.get(name) {
+            Some(instance) => IdHelper::lock(instance).set_options(options),
+            None => {
+                let generator = DefaultIdGenerator::try_new(options)?;
+                registry.insert(name.to_owned(), Arc::new(Mutex::new(generator)));
+                Ok(())
+            }
+        }
+    }
+
+    pub fn unregister(name: &str) -> bool {
+        IdHelper::registry()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(name)
+            .is_some()
+    }
+
+    pub fn add_observer_for(
+        name: &str,
+        observer: Arc<dyn IGenIdObserver>,
+    ) -> Result<(), IdGenError> {
+        let instance = IdHelper::named_instance(name)?;
+        IdHelper::lock(&instance).add_observer(observer);
+        Ok(())
+    }
+
+    pub fn next_id_for(name: &str) -> Result<i64, IdGenError> {
+        let instance = IdHelper::named_instance(name)?;
+        let mut idgen = IdHelper::lock(&instance);
+        idgen.try_next_id()
+    }
+
+    pub fn next_ids_for(name: &str, count: usize) -> Result<Vec<i64>, IdGenError> {
+        let instance = IdHelper::named_instance(name)?;
+        let mut idgen = IdHelper::lock(&instance);
+        idgen.try_next_ids(count)
+    }
+
+    pub async fn next_ids_async_for(name: &str, count: usize) -> Result<Vec<i64>, IdGenError> {
+        let instance = IdHelper::named_instance(name)?;
+        IdHelper::fill_ids_async(&instance, count).await
+    }
+
+    pub fn decode_for(name: &str, id: i64) -> Result<IdParts, IdGenError> {
+        let instance = IdHelper::named_instance(name)?;
+        let idgen = IdHelper::lock(&instance);
+        Ok(idgen.decode(id))
     }
 }
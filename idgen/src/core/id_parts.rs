@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+
+/// 由 [`IdGeneratorOptions::decode`](super::IdGeneratorOptions::decode) 从ID中还原出的各部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdParts {
+    /// 相对 BaseTime 的时间戳（ms单位）
+    pub time_tick: i64,
+    /// Unix 时间戳（ms单位），即 time_tick + BaseTime
+    pub timestamp: i64,
+    /// 机器码
+    pub worker_id: u32,
+    /// 序列数
+    pub seq_number: u32,
+    /// 是否为时间回拨预留位（序列数 1-4）生成的ID
+    pub is_turn_back: bool,
+}
+
+impl IdParts {
+    pub fn datetime(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.timestamp)
+    }
+}
@@ -1,12 +1,21 @@
 mod default_id_generator;
 mod id_helper;
 mod snow_worker;
+mod snow_worker_m2;
 mod id_generator_options;
 mod i_snow_worker;
+mod id_gen_error;
+mod id_parts;
 mod over_cost_action_arg;
+mod gen_id_observer;
 
-pub use over_cost_action_arg::OverCostActionArg;
+pub use over_cost_action_arg::{OverCostActionArg, OverCostActionType};
+pub use gen_id_observer::{GenIdActionCounter, IGenIdObserver, TracingGenIdObserver};
 pub use id_helper::IdHelper;
 pub use default_id_generator::DefaultIdGenerator;
 pub use id_generator_options::IdGeneratorOptions;
+pub use id_gen_error::IdGenError;
+pub use id_parts::IdParts;
 pub use i_snow_worker::ISnowWorker;
+pub use snow_worker::SnowWorker;
+pub use snow_worker_m2::SnowWorkerM2;
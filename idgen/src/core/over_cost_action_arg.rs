@@ -1,8 +1,46 @@
+/// 事件类型，取值与原 C# 版本保持一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverCostActionType {
+    /// 开始漂移
+    BeginOverCost = 1,
+    /// 结束漂移
+    EndOverCost = 2,
+    /// 开始时间回拨
+    BeginTurnBack = 8,
+    /// 结束时间回拨
+    EndTurnBack = 9,
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct OverCostActionArg {
-    action_type: u32,
-    time_tick: i64,
-    worker_id: u16,
-    over_cost_count_in_one_term: i32,
-    gen_count_in_one_term: i32,
-    term_index: i32,
-}
\ No newline at end of file
+    pub action_type: OverCostActionType,
+    /// 事件发生时使用的时间戳（相对 BaseTime，ms单位）
+    pub time_tick: i64,
+    pub worker_id: u32,
+    /// 本轮漂移的漂移次数
+    pub over_cost_count_in_one_term: u32,
+    /// 本轮漂移期间生成的ID数
+    pub gen_count_in_one_term: u32,
+    /// 漂移事件为漂移轮次，时间回拨事件为回拨次序（1-4）
+    pub term_index: u32,
+}
+
+impl OverCostActionArg {
+    pub fn new(
+        action_type: OverCostActionType,
+        time_tick: i64,
+        worker_id: u32,
+        over_cost_count_in_one_term: u32,
+        gen_count_in_one_term: u32,
+        term_index: u32,
+    ) -> Self {
+        Self {
+            action_type,
+            time_tick,
+            worker_id,
+            over_cost_count_in_one_term,
+            gen_count_in_one_term,
+            term_index,
+        }
+    }
+}
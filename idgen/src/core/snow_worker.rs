@@ -1,9 +1,13 @@
 use chrono::Utc;
-use std::thread::sleep;
+use std::sync::Arc;
 
-use super::{IdGeneratorOptions, OverCostActionArg};
+use super::id_generator_options::DEFAULT_BASE_TIME;
+use super::{
+    IGenIdObserver, ISnowWorker, IdGenError, IdGeneratorOptions, OverCostActionArg,
+    OverCostActionType,
+};
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct SnowWorker {
     ///基础时间
     pub base_time: i64,
@@ -21,14 +25,16 @@ pub struct SnowWorker {
     pub top_over_cost_count: u32,
 
     timestamp_shift: u8,
-    current_seq_number: u32,
-    last_time_tick: i64,
-    turn_back_time_tick: i64,
+    pub(super) current_seq_number: u32,
+    pub(super) last_time_tick: i64,
+    pub(super) turn_back_time_tick: i64,
     turn_back_index: u8,
     is_over_cost: bool,
     over_cost_count_in_one_term: u32,
     gen_count_in_one_term: u32,
     term_index: u32,
+
+    observers: Vec<Arc<dyn IGenIdObserver>>,
 }
 
 impl SnowWorker {
@@ -37,99 +43,51 @@ impl SnowWorker {
         return SnowWorker::new(options);
     }
 
-    pub fn set_options(&mut self, options: IdGeneratorOptions) {
-        // 1.BaseTime
-        if options.base_time == 0 {
-            self.base_time = 1582136402000;
-        } else if options.base_time < 631123200000
-            || options.base_time > Utc::now().timestamp_millis()
-        {
-            panic!("BaseTime error.");
-        } else {
-            self.base_time = options.base_time;
-        }
-
-        // 2.WorkerIdBitLength
-        if options.worker_id_bit_length <= 0 {
-            panic!("WorkerIdBitLength error.(range:[1, 21])");
-        }
-        if options.seq_bit_length + options.worker_id_bit_length > 22 {
-            panic!("error：WorkerIdBitLength + SeqBitLength <= 22");
-        } else {
-            // self.WorkerIdBitLength = options.WorkerIdBitLength;
-            self.worker_id_bit_length = if options.worker_id_bit_length <= 0 {
-                6
-            } else {
-                options.worker_id_bit_length
-            };
-        }
-
-        // 3.WorkerId
-        let mut max_worker_id_number = (1 << options.worker_id_bit_length) - 1;
-        if max_worker_id_number == 0 {
-            max_worker_id_number = 63;
-        }
-        if options.worker_id > max_worker_id_number {
-            panic!("WorkerId error. (range:[0, {} ]", max_worker_id_number);
-        } else {
-            self.worker_id = options.worker_id;
-        }
+    /// 重新配置时沿用已发号的时间戳，当前毫秒视为已用尽，之后的ID不会与配置前重复
+    pub fn set_options(&mut self, options: IdGeneratorOptions) -> Result<(), IdGenError> {
+        options.validate()?;
+        let last_timestamp = self.last_timestamp();
+        let turn_back_timestamp =
+            (self.turn_back_time_tick > 0).then(|| self.base_time + self.turn_back_time_tick);
+
+        self.base_time = options.effective_base_time();
+        self.worker_id_bit_length = options.worker_id_bit_length;
+        self.worker_id = options.worker_id;
+        self.seq_bit_length = options.seq_bit_length;
+        self.max_seq_number = options.effective_max_seq_number();
+        self.min_seq_number = options.min_seq_number;
+        self.top_over_cost_count = options.top_over_cost_count;
 
-        // 4.SeqBitLength
-        if options.seq_bit_length < 2 || options.seq_bit_length > 21 {
-            panic!("SeqBitLength error. (range:[2, 21])");
-        } else {
-            // self.SeqBitLength = options.SeqBitLength;
-            self.seq_bit_length = if options.seq_bit_length <= 0 {
-                6
-            } else {
-                options.seq_bit_length
-            };
-        }
-
-        // 5.MaxSeqNumber
-        let mut max_seq_number = (1 << options.seq_bit_length) - 1;
-        if options.max_seq_number <= 0 {
-            max_seq_number = 63;
-        }
-        if options.max_seq_number > max_seq_number {
-            panic!("MaxSeqNumber error. (range:[1, {}]", max_seq_number);
-        } else {
-            self.max_seq_number = if options.max_seq_number == 0 {
-                max_seq_number
-            } else {
-                options.max_seq_number
-            };
+        self.timestamp_shift = self.worker_id_bit_length + self.seq_bit_length;
+        self.current_seq_number = self.min_seq_number;
+        if let Some(timestamp) = last_timestamp {
+            self.resume_after(timestamp);
         }
-
-        // 6.MinSeqNumber
-        if options.min_seq_number < 5 || options.min_seq_number > max_seq_number {
-            panic!("MinSeqNumber error. (range:[5, {}]", max_seq_number);
-        } else {
-            self.min_seq_number = options.min_seq_number;
-            // self.MinSeqNumber = if options.MinSeqNumber <= 0 { 5 } else { options.MinSeqNumber };
-        }
-
-        // 7.TopOverCostCount
-        //self.TopOverCostCount = if options.TopOverCostCount == 0 { 2000 } else { options.TopOverCostCount };
-        if options.top_over_cost_count > 10000 {
-            panic!("TopOverCostCount error. (range:[0, 10000]");
-        } else {
-            self.top_over_cost_count = options.top_over_cost_count;
+        if let Some(timestamp) = turn_back_timestamp {
+            self.turn_back_time_tick = timestamp - self.base_time;
         }
+        Ok(())
+    }
 
-        // 8.Others
-        self.timestamp_shift = self.worker_id_bit_length + self.seq_bit_length;
-        self.current_seq_number = self.min_seq_number;
+    /// 最后一次发号使用的时间戳（毫秒，漂移时可能超前于系统时间），尚未发号时为 None
+    pub fn last_timestamp(&self) -> Option<i64> {
+        (self.last_time_tick > 0).then(|| self.base_time + self.last_time_tick)
+    }
 
-        if options.method == 1 {
-            sleep(std::time::Duration::from_millis(500));
-        }
+    /// 从该时间戳之后继续发号，该毫秒的序列数视为已用尽
+    pub fn resume_after(&mut self, timestamp: i64) {
+        self.last_time_tick = timestamp - self.base_time;
+        self.current_seq_number = self.max_seq_number + 1;
     }
 
+    /// 配置不合法时 panic，需要处理错误请使用 [`SnowWorker::try_new`]
     pub fn new(options: IdGeneratorOptions) -> SnowWorker {
+        SnowWorker::try_new(options).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_new(options: IdGeneratorOptions) -> Result<SnowWorker, IdGenError> {
         let mut worker = SnowWorker {
-            base_time: 1582136402000,
+            base_time: DEFAULT_BASE_TIME,
             worker_id_bit_length: 0,
             worker_id: 0,
             seq_bit_length: 0,
@@ -146,36 +104,107 @@ impl SnowWorker {
             over_cost_count_in_one_term: 0,
             gen_count_in_one_term: 0,
             term_index: 0,
+
+            observers: Vec::new(),
         };
 
-        worker.set_options(options);
-        return worker;
+        worker.set_options(options)?;
+        Ok(worker)
     }
 
-    pub fn next_id(&mut self) -> i64 {
-        // println!("SeqBitLength: {}", self.SeqBitLength);
+    /// 不阻塞地生成下一个ID，漂移次数达到上限需要等待时间追上时返回 None
+    pub fn poll_next_id(&mut self) -> Option<i64> {
         if self.is_over_cost {
             self.next_over_cost_id()
         } else {
-            self.next_normal_id()
+            Some(self.next_normal_id())
         }
     }
 
-    fn do_gen_id_action(&self, _arg: OverCostActionArg) {}
+    pub fn next_id(&mut self) -> i64 {
+        ISnowWorker::next_id(self)
+    }
+
+    pub fn add_observer(&mut self, observer: Arc<dyn IGenIdObserver>) {
+        self.observers.push(observer);
+    }
 
-    fn begin_over_cost_action(&self, _use_time_tick: i64) {}
+    fn do_gen_id_action(&self, arg: OverCostActionArg) {
+        for observer in self.observers.iter() {
+            observer.on_gen_id_action(&arg);
+        }
+    }
 
-    fn end_over_cost_action(&mut self, _use_time_tick: i64) {
-        // if self._TermIndex > 10000 {
-        //     self._TermIndex = 0;
-        // }
+    fn over_cost_action_arg(
+        &self,
+        action_type: OverCostActionType,
+        use_time_tick: i64,
+    ) -> OverCostActionArg {
+        OverCostActionArg::new(
+            action_type,
+            use_time_tick,
+            self.worker_id,
+            self.over_cost_count_in_one_term,
+            self.gen_count_in_one_term,
+            self.term_index,
+        )
     }
 
-    fn begin_turn_back_action(&self, _use_time_tick: i64) {}
+    fn turn_back_action_arg(
+        &self,
+        action_type: OverCostActionType,
+        use_time_tick: i64,
+    ) -> OverCostActionArg {
+        OverCostActionArg::new(
+            action_type,
+            use_time_tick,
+            self.worker_id,
+            0,
+            0,
+            self.turn_back_index as u32,
+        )
+    }
 
-    fn end_turn_back_action(&self, _use_time_tick: i64) {}
+    fn begin_over_cost_action(&self, use_time_tick: i64) {
+        if self.observers.is_empty() {
+            return;
+        }
+        self.do_gen_id_action(
+            self.over_cost_action_arg(OverCostActionType::BeginOverCost, use_time_tick),
+        );
+    }
 
-    fn next_over_cost_id(&mut self) -> i64 {
+    fn end_over_cost_action(&mut self, use_time_tick: i64) {
+        if self.term_index > 10000 {
+            self.term_index = 0;
+        }
+        if self.observers.is_empty() {
+            return;
+        }
+        self.do_gen_id_action(
+            self.over_cost_action_arg(OverCostActionType::EndOverCost, use_time_tick),
+        );
+    }
+
+    pub(super) fn begin_turn_back_action(&self, use_time_tick: i64) {
+        if self.observers.is_empty() {
+            return;
+        }
+        self.do_gen_id_action(
+            self.turn_back_action_arg(OverCostActionType::BeginTurnBack, use_time_tick),
+        );
+    }
+
+    pub(super) fn end_turn_back_action(&self, use_time_tick: i64) {
+        if self.observers.is_empty() {
+            return;
+        }
+        self.do_gen_id_action(
+            self.turn_back_action_arg(OverCostActionType::EndTurnBack, use_time_tick),
+        );
+    }
+
+    fn next_over_cost_id(&mut self) -> Option<i64> {
         let current_time_tick = self.get_current_time_tick();
 
         if current_time_tick > self.last_time_tick {
@@ -187,19 +216,12 @@ impl SnowWorker {
             self.over_cost_count_in_one_term = 0;
             self.gen_count_in_one_term = 0;
 
-            return self.calc_id(self.last_time_tick);
+            return Some(self.calc_id(self.last_time_tick));
         }
 
+        // 漂移次数达到上限，等待系统时间追上后由上面的分支结束漂移
         if self.over_cost_count_in_one_term >= self.top_over_cost_count {
-            self.end_over_cost_action(current_time_tick);
-
-            self.last_time_tick = self.get_next_time_tick();
-            self.current_seq_number = self.min_seq_number;
-            self.is_over_cost = false;
-            self.over_cost_count_in_one_term = 0;
-            self.gen_count_in_one_term = 0;
-
-            return self.calc_id(self.last_time_tick);
+            return None;
         }
 
         if self.current_seq_number > self.max_seq_number {
@@ -209,11 +231,11 @@ impl SnowWorker {
             self.over_cost_count_in_one_term += 1;
             self.gen_count_in_one_term += 1;
 
-            return self.calc_id(self.last_time_tick);
+            return Some(self.calc_id(self.last_time_tick));
         }
 
         self.gen_count_in_one_term += 1;
-        return self.calc_id(self.last_time_tick);
+        return Some(self.calc_id(self.last_time_tick));
     }
 
     fn next_normal_id(&mut self) -> i64 {
@@ -249,8 +271,6 @@ impl SnowWorker {
         }
 
         if self.current_seq_number > self.max_seq_number {
-            self.begin_over_cost_action(current_time_tick);
-
             self.term_index += 1;
             self.last_time_tick += 1;
             self.current_seq_number = self.min_seq_number;
@@ -258,13 +278,16 @@ impl SnowWorker {
             self.over_cost_count_in_one_term = 1;
             self.gen_count_in_one_term = 1;
 
+            // 先进入新一轮漂移再通知，观察者拿到的是本轮的轮次和计数
+            self.begin_over_cost_action(current_time_tick);
+
             return self.calc_id(self.last_time_tick);
         }
 
         return self.calc_id(self.last_time_tick);
     }
 
-    fn calc_id(&mut self, use_time_tick: i64) -> i64 {
+    pub(super) fn calc_id(&mut self, use_time_tick: i64) -> i64 {
         let result = (use_time_tick << self.timestamp_shift)
             + (self.worker_id << self.seq_bit_length) as i64
             + (self.current_seq_number) as i64;
@@ -280,19 +303,104 @@ impl SnowWorker {
         return result;
     }
 
-    fn get_current_time_tick(&self) -> i64 {
+    pub(super) fn get_current_time_tick(&self) -> i64 {
         return Utc::now().timestamp_millis() - self.base_time;
     }
+}
+
+impl ISnowWorker for SnowWorker {
+    fn set_options(&mut self, options: IdGeneratorOptions) -> Result<(), IdGenError> {
+        SnowWorker::set_options(self, options)
+    }
+
+    fn last_timestamp(&self) -> Option<i64> {
+        SnowWorker::last_timestamp(self)
+    }
+
+    fn resume_after(&mut self, timestamp: i64) {
+        SnowWorker::resume_after(self, timestamp);
+    }
+
+    fn poll_next_id(&mut self) -> Option<i64> {
+        SnowWorker::poll_next_id(self)
+    }
+
+    fn add_observer(&mut self, observer: Arc<dyn IGenIdObserver>) {
+        SnowWorker::add_observer(self, observer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::SnowWorker;
+    use crate::core::{
+        DefaultIdGenerator, IGenIdObserver, IdGeneratorOptions, OverCostActionArg,
+        OverCostActionType,
+    };
 
-    fn get_next_time_tick(&self) -> i64 {
-        let mut temp_time_ticker = self.get_current_time_tick();
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<OverCostActionArg>>);
 
-        while temp_time_ticker <= self.last_time_tick {
-            // 暂停1ms
-            sleep(std::time::Duration::from_millis(1));
-            temp_time_ticker = self.get_current_time_tick();
+    impl IGenIdObserver for Recorder {
+        fn on_gen_id_action(&self, arg: &OverCostActionArg) {
+            self.0.lock().unwrap().push(*arg);
         }
+    }
+
+    #[test]
+    fn over_cost_observers_receive_current_term() {
+        let recorder = Arc::new(Recorder::default());
+        let mut worker = SnowWorker::new(IdGeneratorOptions::new(1));
+        worker.add_observer(recorder.clone());
+        // 每毫秒只有 59 个序列数，连续发号必然漂移，直到漂移结束
+        let mut count = 0;
+        while !recorder
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|arg| arg.action_type == OverCostActionType::EndOverCost)
+        {
+            worker.next_id();
+            count += 1;
+            assert!(count < 10_000_000, "over cost never ended");
+        }
+
+        let events = recorder.0.lock().unwrap();
+        let begin = events
+            .iter()
+            .find(|arg| arg.action_type == OverCostActionType::BeginOverCost)
+            .unwrap();
+        assert_eq!(begin.term_index, 1);
+        assert_eq!(begin.over_cost_count_in_one_term, 1);
+        assert_eq!(begin.gen_count_in_one_term, 1);
+        assert_eq!(begin.worker_id, 1);
+
+        let end = events.last().unwrap();
+        assert_eq!(end.action_type, OverCostActionType::EndOverCost);
+        assert_eq!(end.term_index, 1);
+        assert!(end.over_cost_count_in_one_term >= 1);
+        assert!(end.gen_count_in_one_term >= end.over_cost_count_in_one_term);
+        assert!(end.time_tick > begin.time_tick);
+    }
+
+    #[test]
+    fn set_options_does_not_reissue_ids() {
+        let mut worker = SnowWorker::new(IdGeneratorOptions::new(1));
+        let last = (0..1000).map(|_| worker.next_id()).max().unwrap();
+        worker.set_options(IdGeneratorOptions::new(1)).unwrap();
+        assert!(worker.next_id() > last);
+    }
 
-        return temp_time_ticker;
+    #[test]
+    fn switching_method_resumes_after_last_id() {
+        let mut generator = DefaultIdGenerator::new(IdGeneratorOptions::new(1));
+        let last = generator.next_ids(1000).into_iter().max().unwrap();
+        let mut options = IdGeneratorOptions::new(1);
+        options.method = 2;
+        generator.set_options(options).unwrap();
+        assert!(generator.next_id() > last);
     }
 }
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use super::{IGenIdObserver, ISnowWorker, IdGenError, IdGeneratorOptions, SnowWorker};
+
+/// 传统雪花算法（Method = 2）
+///
+/// 序列数用尽时等待下一毫秒，不做漂移；时间回拨时不发号，阻塞直到系统时间追上上次使用的时间戳。
+#[derive(Clone)]
+pub struct SnowWorkerM2 {
+    worker: SnowWorker,
+}
+
+impl SnowWorkerM2 {
+    /// 配置不合法时 panic，需要处理错误请使用 [`SnowWorkerM2::try_new`]
+    pub fn new(options: IdGeneratorOptions) -> SnowWorkerM2 {
+        SnowWorkerM2 {
+            worker: SnowWorker::new(options),
+        }
+    }
+
+    pub fn try_new(options: IdGeneratorOptions) -> Result<SnowWorkerM2, IdGenError> {
+        Ok(SnowWorkerM2 {
+            worker: SnowWorker::try_new(options)?,
+        })
+    }
+
+    pub fn set_options(&mut self, options: IdGeneratorOptions) -> Result<(), IdGenError> {
+        self.worker.set_options(options)
+    }
+
+    pub fn last_timestamp(&self) -> Option<i64> {
+        self.worker.last_timestamp()
+    }
+
+    pub fn resume_after(&mut self, timestamp: i64) {
+        self.worker.resume_after(timestamp);
+    }
+
+    /// 不阻塞地生成下一个ID，序列数用尽或时间回拨时返回 None
+    pub fn poll_next_id(&mut self) -> Option<i64> {
+        let current_time_tick = self.worker.get_current_time_tick();
+
+        // 时间回拨：等待系统时间追平，避免与回拨前的ID重复
+        if current_time_tick < self.worker.last_time_tick {
+            if self.worker.turn_back_time_tick < 1 {
+                self.worker.turn_back_time_tick = self.worker.last_time_tick;
+                self.worker
+                    .begin_turn_back_action(self.worker.turn_back_time_tick);
+            }
+            return None;
+        }
+
+        if self.worker.turn_back_time_tick > 0 {
+            self.worker
+                .end_turn_back_action(self.worker.turn_back_time_tick);
+            self.worker.turn_back_time_tick = 0;
+        }
+
+        if current_time_tick == self.worker.last_time_tick {
+            // 序列数用尽：等待下一毫秒
+            if self.worker.current_seq_number > self.worker.max_seq_number {
+                return None;
+            }
+        } else {
+            self.worker.current_seq_number = self.worker.min_seq_number;
+        }
+
+        self.worker.last_time_tick = current_time_tick;
+        Some(self.worker.calc_id(current_time_tick))
+    }
+
+    pub fn next_id(&mut self) -> i64 {
+        ISnowWorker::next_id(self)
+    }
+
+    pub fn add_observer(&mut self, observer: Arc<dyn IGenIdObserver>) {
+        self.worker.add_observer(observer);
+    }
+}
+
+impl ISnowWorker for SnowWorkerM2 {
+    fn set_options(&mut self, options: IdGeneratorOptions) -> Result<(), IdGenError> {
+        SnowWorkerM2::set_options(self, options)
+    }
+
+    fn last_timestamp(&self) -> Option<i64> {
+        SnowWorkerM2::last_timestamp(self)
+    }
+
+    fn resume_after(&mut self, timestamp: i64) {
+        SnowWorkerM2::resume_after(self, timestamp);
+    }
+
+    fn poll_next_id(&mut self) -> Option<i64> {
+        SnowWorkerM2::poll_next_id(self)
+    }
+
+    fn add_observer(&mut self, observer: Arc<dyn IGenIdObserver>) {
+        SnowWorkerM2::add_observer(self, observer);
+    }
+}
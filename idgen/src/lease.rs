@@ -0,0 +1,216 @@
+use std::sync::{
+    atomic::{AtomicI64, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use bb8_redis::{bb8::Pool, RedisConnectionManager};
+use chrono::Utc;
+use redis::Script;
+use tokio::task::JoinHandle;
+
+use crate::{IdGenError, IdGeneratorOptions};
+
+/// 仅当 key 仍归自己持有时续期
+const RENEW_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+/// 仅当 key 仍归自己持有时删除，避免误删他人重新申请到的租约
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
+
+#[derive(Debug, Clone)]
+pub struct WorkerIdLeaseOptions {
+    /// 租约 key 前缀，实际 key 为 `{key_prefix}:{worker_id}`
+    pub key_prefix: String,
+    /// 租约有效期
+    pub ttl: Duration,
+    /// 续期间隔，需明显小于 ttl
+    pub renew_interval: Duration,
+}
+
+impl Default for WorkerIdLeaseOptions {
+    fn default() -> Self {
+        Self {
+            key_prefix: "idgen:worker".to_owned(),
+            ttl: Duration::from_secs(30),
+            renew_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// 租约状态的只读句柄，交给生成器在发号前检查
+#[derive(Debug, Clone)]
+pub struct LeaseHandle {
+    worker_id: u32,
+    /// 本地估算的租约到期时间（Unix ms），0 表示已失效
+    expires_at: Arc<AtomicI64>,
+}
+
+impl LeaseHandle {
+    pub fn worker_id(&self) -> u32 {
+        self.worker_id
+    }
+
+    pub fn is_valid(&self) -> bool {
+        Utc::now().timestamp_millis() < self.expires_at.load(Ordering::Acquire)
+    }
+
+    fn renewed(&self, expires_at: i64) {
+        self.expires_at.store(expires_at, Ordering::Release);
+    }
+
+    fn expire(&self) {
+        self.expires_at.store(0, Ordering::Release);
+    }
+}
+
+/// 基于 Redis 的机器码租约
+///
+/// 申请时从 0 开始依次尝试 `SET key owner NX PX ttl`，成功后在后台定期续期；
+/// 续期发现 key 已不属于自己，或网络异常导致超过有效期未续上，租约即视为丢失。
+pub struct WorkerIdLease {
+    pool: Pool<RedisConnectionManager>,
+    key: String,
+    owner: String,
+    handle: LeaseHandle,
+    renew_task: JoinHandle<()>,
+}
+
+impl WorkerIdLease {
+    /// 在 `[0, 2^WorkerIdBitLength-1]` 范围内申请一个空闲机器码
+    pub async fn acquire(
+        pool: Pool<RedisConnectionManager>,
+        id_options: &IdGeneratorOptions,
+        options: WorkerIdLeaseOptions,
+    ) -> Result<WorkerIdLease, IdGenError> {
+        id_options.validate()?;
+        let max_worker_id: u32 = (1 << id_options.worker_id_bit_length) - 1;
+        let owner = uuid::Uuid::new_v4().to_string();
+        let ttl_millis = options.ttl.as_millis() as i64;
+
+        let mut conn = pool
+            .get()
+            .await
+            .map_err(|e| IdGenError::Redis(e.to_string()))?;
+        for worker_id in 0..=max_worker_id {
+            let key = format!("{}:{}", options.key_prefix, worker_id);
+            let started = Utc::now().timestamp_millis();
+            let claimed: Option<String> = redis::cmd("SET")
+                .arg(&key)
+                .arg(&owner)
+                .arg("NX")
+                .arg("PX")
+                .arg(ttl_millis)
+                .query_async(&mut *conn)
+                .await
+                .map_err(|e| IdGenError::Redis(e.to_string()))?;
+            if claimed.is_none() {
+                continue;
+            }
+
+            let handle = LeaseHandle {
+                worker_id,
+                expires_at: Arc::new(AtomicI64::new(started + ttl_millis)),
+            };
+            let renew_task = tokio::spawn(renew_loop(
+                pool.clone(),
+                key.clone(),
+                owner.clone(),
+                options,
+                handle.clone(),
+            ));
+            return Ok(WorkerIdLease {
+                pool: pool.clone(),
+                key,
+                owner,
+                handle,
+                renew_task,
+            });
+        }
+        Err(IdGenError::NoFreeWorkerId(max_worker_id))
+    }
+
+    pub fn worker_id(&self) -> u32 {
+        self.handle.worker_id
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.handle.is_valid()
+    }
+
+    pub fn handle(&self) -> LeaseHandle {
+        self.handle.clone()
+    }
+
+    /// 停止续期并删除租约 key，服务退出前调用
+    pub async fn release(self) -> Result<(), IdGenError> {
+        self.renew_task.abort();
+        self.handle.expire();
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| IdGenError::Redis(e.to_string()))?;
+        let _: i64 = Script::new(RELEASE_SCRIPT)
+            .key(&self.key)
+            .arg(&self.owner)
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| IdGenError::Redis(e.to_string()))?;
+        Ok(())
+    }
+}
+
+impl Drop for WorkerIdLease {
+    fn drop(&mut self) {
+        // 未调用 release 时仅停止续期，key 在 ttl 后自然过期
+        self.renew_task.abort();
+    }
+}
+
+async fn renew_loop(
+    pool: Pool<RedisConnectionManager>,
+    key: String,
+    owner: String,
+    options: WorkerIdLeaseOptions,
+    handle: LeaseHandle,
+) {
+    let script = Script::new(RENEW_SCRIPT);
+    let ttl_millis = options.ttl.as_millis() as i64;
+    loop {
+        tokio::time::sleep(options.renew_interval).await;
+
+        let started = Utc::now().timestamp_millis();
+        let mut conn = match pool.get().await {
+            Ok(conn) => conn,
+            // 连接异常时保留原有效期，超时后租约自然失效
+            Err(_) => continue,
+        };
+        let renewed: Result<i64, _> = script
+            .key(&key)
+            .arg(&owner)
+            .arg(ttl_millis)
+            .invoke_async(&mut *conn)
+            .await;
+        match renewed {
+            Ok(1) => handle.renewed(started + ttl_millis),
+            // key 已过期或被其他实例占用，不再续期
+            Ok(_) => {
+                handle.expire();
+                return;
+            }
+            Err(_) => continue,
+        }
+    }
+}
@@ -1,22 +1,51 @@
 pub mod core;
+pub mod lease;
 
 pub use core::*;
 
-pub fn set_id_generator(options: IdGeneratorOptions) {
-    IdHelper::set_id_generator(options);
+pub fn set_id_generator(options: IdGeneratorOptions) -> Result<(), IdGenError> {
+    IdHelper::set_id_generator(options)
 }
 
-pub fn set_options(worker_id: u32, worker_id_bit_length: u8, seq_bit_length: u8) {
+pub fn set_options(
+    worker_id: u32,
+    worker_id_bit_length: u8,
+    seq_bit_length: u8,
+) -> Result<(), IdGenError> {
     let mut options = IdGeneratorOptions::new(worker_id);
     options.worker_id_bit_length = worker_id_bit_length;
     options.seq_bit_length = seq_bit_length;
-    IdHelper::set_id_generator(options);
+    IdHelper::set_id_generator(options)
 }
 
-pub fn set_worker_id(worker_id: u32) {
-    IdHelper::set_worker_id(worker_id);
+pub fn set_worker_id(worker_id: u32) -> Result<(), IdGenError> {
+    IdHelper::set_worker_id(worker_id)
+}
+
+pub fn try_next_id() -> Result<i64, IdGenError> {
+    IdHelper::try_next_id()
 }
 
 pub fn next_id() -> i64 {
     IdHelper::next_id()
 }
+
+pub fn try_next_ids(count: usize) -> Result<Vec<i64>, IdGenError> {
+    IdHelper::try_next_ids(count)
+}
+
+pub fn next_ids(count: usize) -> Vec<i64> {
+    IdHelper::next_ids(count)
+}
+
+pub async fn next_id_async() -> Result<i64, IdGenError> {
+    IdHelper::next_id_async().await
+}
+
+pub async fn next_ids_async(count: usize) -> Result<Vec<i64>, IdGenError> {
+    IdHelper::next_ids_async(count).await
+}
+
+pub fn decode(id: i64) -> IdParts {
+    IdHelper::decode(id)
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use bb8_redis::{bb8::Pool, RedisConnectionManager};
+use idgen::lease::{WorkerIdLease, WorkerIdLeaseOptions};
+use idgen::{IdGenError, IdGeneratorOptions, IdHelper};
+
+async fn redis_pool() -> Pool<RedisConnectionManager> {
+    let redis_url = std::env::var("REDIS_URL").expect("REDIS_URL not set");
+    let manager = RedisConnectionManager::new(redis_url).unwrap();
+    Pool::builder().build(manager).await.unwrap()
+}
+
+/// 每个测试使用独立的 key 前缀，互不争用机器码
+fn lease_options() -> WorkerIdLeaseOptions {
+    WorkerIdLeaseOptions {
+        key_prefix: format!("idgen:test:{}", uuid::Uuid::new_v4()),
+        ttl: Duration::from_millis(600),
+        renew_interval: Duration::from_millis(200),
+    }
+}
+
+/// 只有 0 和 1 两个机器码
+fn two_worker_ids() -> IdGeneratorOptions {
+    let mut options = IdGeneratorOptions::new(0);
+    options.worker_id_bit_length = 1;
+    options
+}
+
+async fn delete_key(pool: &Pool<RedisConnectionManager>, key: &str) {
+    let mut conn = pool.get().await.unwrap();
+    let _: i64 = redis::cmd("DEL")
+        .arg(key)
+        .query_async(&mut *conn)
+        .await
+        .unwrap();
+}
+
+/// 需要本地 redis-server：`REDIS_URL=redis://127.0.0.1/ cargo test -p idgen -- --ignored`
+#[tokio::test]
+#[ignore]
+async fn worker_ids_are_exclusive_until_released() {
+    let pool = redis_pool().await;
+    let options = lease_options();
+    let first = WorkerIdLease::acquire(pool.clone(), &two_worker_ids(), options.clone())
+        .await
+        .unwrap();
+    let second = WorkerIdLease::acquire(pool.clone(), &two_worker_ids(), options.clone())
+        .await
+        .unwrap();
+    assert_eq!((first.worker_id(), second.worker_id()), (0, 1));
+    assert!(matches!(
+        WorkerIdLease::acquire(pool.clone(), &two_worker_ids(), options.clone()).await,
+        Err(IdGenError::NoFreeWorkerId(1))
+    ));
+
+    // 续期使租约在 ttl 之后仍然有效
+    tokio::time::sleep(options.ttl * 2).await;
+    assert!(first.is_valid() && second.is_valid());
+
+    first.release().await.unwrap();
+    let third = WorkerIdLease::acquire(pool.clone(), &two_worker_ids(), options)
+        .await
+        .unwrap();
+    assert_eq!(third.worker_id(), 0);
+    second.release().await.unwrap();
+    third.release().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn generator_refuses_ids_after_lease_is_lost() {
+    let pool = redis_pool().await;
+    let options = lease_options();
+    let lease = WorkerIdLease::acquire(pool.clone(), &two_worker_ids(), options.clone())
+        .await
+        .unwrap();
+    IdHelper::set_id_generator_with_lease(two_worker_ids(), &lease).unwrap();
+    let id = IdHelper::try_next_id().unwrap();
+    assert_eq!(IdHelper::decode(id).worker_id, lease.worker_id());
+
+    // 模拟 key 过期，下次续期发现 key 已不属于自己后租约失效
+    delete_key(
+        &pool,
+        &format!("{}:{}", options.key_prefix, lease.worker_id()),
+    )
+    .await;
+    tokio::time::sleep(options.renew_interval * 2).await;
+    assert!(!lease.is_valid());
+    assert!(matches!(
+        IdHelper::try_next_id(),
+        Err(IdGenError::LeaseLost(worker_id)) if worker_id == lease.worker_id()
+    ));
+}
@@ -0,0 +1,43 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+use idgen::IdGeneratorOptions;
+
+const THREADS: usize = 8;
+const IDS_PER_THREAD: usize = 20_000;
+
+/// 多线程并发发号，期间反复重新配置全局生成器（含切换雪花算法），所有ID都不能重复
+#[test]
+fn ids_stay_unique_across_reconfiguration() {
+    idgen::set_id_generator(IdGeneratorOptions::new(1)).unwrap();
+    let barrier = Arc::new(Barrier::new(THREADS + 1));
+    let workers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                (0..IDS_PER_THREAD)
+                    .map(|_| idgen::next_id())
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    barrier.wait();
+    for i in 0..20 {
+        thread::sleep(Duration::from_millis(2));
+        let mut options = IdGeneratorOptions::new(1);
+        options.method = if i % 2 == 0 { 1 } else { 2 };
+        idgen::set_id_generator(options).unwrap();
+    }
+
+    let mut ids = HashSet::new();
+    for worker in workers {
+        for id in worker.join().unwrap() {
+            assert!(ids.insert(id), "duplicate id: {}", id);
+        }
+    }
+    assert_eq!(ids.len(), THREADS * IDS_PER_THREAD);
+}
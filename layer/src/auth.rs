@@ -19,7 +19,7 @@ use serde_json::{json, Value};
 use std::str::FromStr;
 
 use entity::{auth::Account, middleware::Claims, state::OpenApiState};
-use idgen::next_id;
+use idgen::try_next_id;
 
 lazy_static::lazy_static! {
     static ref PRIVATE_KEY: EncodingKey = EncodingKey::from_ed_pem(include_bytes!("../../private.pem")).unwrap();
@@ -96,7 +96,14 @@ pub async fn auth_middleware(
                 };
 
                 let now = Utc::now();
-                let snow_id = next_id();
+                let snow_id = match try_next_id() {
+                    Ok(snow_id) => snow_id,
+                    Err(e) => {
+                        log!("{}", e);
+                        return (StatusCode::INTERNAL_SERVER_ERROR, "session id generate failed.")
+                            .into_response()
+                    }
+                };
 
                 let mut new_parts = Parts::from(parts);
                 let (access_token, access_duration) =
@@ -1,11 +1,15 @@
 use axum::{
-    extract::{FromRequestParts, Extension},
+    async_trait,
+    extract::{Extension, FromRequestParts},
+    http::{header, request::Parts, StatusCode},
     middleware::from_extractor,
     Router,
-    http::{header, StatusCode, request::Parts},
-    async_trait,
 };
 use bb8_redis::{bb8::Pool, RedisConnectionManager};
+use idgen::{
+    lease::{WorkerIdLease, WorkerIdLeaseOptions},
+    IdGenError, IdGeneratorOptions, IdHelper,
+};
 use sea_orm::{Database, DatabaseConnection};
 
 use pool::grpc::person_center::PersonCenterGrpcClientManager;
@@ -34,7 +38,11 @@ use pool::grpc::person_center::PersonCenterGrpcClientManager;
 // }
 
 pub async fn sea_orm_connect_extension() -> Extension<DatabaseConnection> {
-    Extension(Database::connect(std::env::var("DATABASE_URL").unwrap()).await.unwrap())
+    Extension(
+        Database::connect(std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap(),
+    )
 }
 
 pub async fn redis_connect_extension() -> Extension<Pool<RedisConnectionManager>> {
@@ -43,6 +51,18 @@ pub async fn redis_connect_extension() -> Extension<Pool<RedisConnectionManager>
     Extension(pool)
 }
 
+/// 用 redis 连接池申请机器码租约并配置全局ID生成器，options 中的 WorkerId 以租约为准
+///
+/// 返回的租约需持有到服务退出，丢弃后不再续期，过期后 `try_next_id` 返回错误。
+pub async fn id_generator_lease(
+    pool: Pool<RedisConnectionManager>,
+    options: IdGeneratorOptions,
+) -> Result<WorkerIdLease, IdGenError> {
+    let lease = WorkerIdLease::acquire(pool, &options, WorkerIdLeaseOptions::default()).await?;
+    IdHelper::set_id_generator_with_lease(options, &lease)?;
+    Ok(lease)
+}
+
 pub async fn person_center_grpc_extension() -> Extension<Pool<PersonCenterGrpcClientManager>> {
     let manager = PersonCenterGrpcClientManager::new("127.0.0.1:8081").unwrap();
     let pool = Pool::builder().build(manager).await.unwrap();
@@ -65,4 +85,4 @@ pub async fn person_center_grpc_extension() -> Extension<Pool<PersonCenterGrpcCl
 //     .route("/", get(handler))
 //     .route("/foo", post(other_handler))
 //     // The extractor will run before all routes
-//     .route_layer(from_extractor::<RequireAuth>());
\ No newline at end of file
+//     .route_layer(from_extractor::<RequireAuth>());
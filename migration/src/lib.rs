@@ -1,6 +1,7 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_table;
+mod m20261018_000001_create_snow_id_function;
 mod utils;
 
 pub struct Migrator;
@@ -10,6 +11,7 @@ impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
             Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20261018_000001_create_snow_id_function::Migration),
         ]
     }
 }
@@ -0,0 +1,113 @@
+use bb8_postgres::tokio_postgres::GenericClient;
+use volo_grpc::Status;
+
+use entity::graph::{
+    ensure_graph, ensure_policy_class,
+    policy::{export_policy, import_policy, PolicyDocument, PolicyFormat},
+    render::GraphView,
+};
+use person_center::bootstrap::{connect_pool, default_policy_class, load_operations};
+use pool::age::{AgeClientExtend, Client};
+
+const USAGE: &str = "usage:
+    ngac_policy export [--json] [FILE]
+    ngac_policy import FILE [--prune] [--dry-run]
+    ngac_policy render [--graphml] [--from ID] [FILE]";
+
+async fn run(args: &[String]) -> Result<(), Status> {
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let from = match args.iter().position(|arg| arg == "--from") {
+        Some(i) => Some(
+            args.get(i + 1)
+                .and_then(|id| id.parse::<i64>().ok())
+                .ok_or_else(|| Status::invalid_argument(USAGE))?,
+        ),
+        None => None,
+    };
+    let file = args
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find(|(i, arg)| !arg.starts_with("--") && args[i - 1] != "--from")
+        .map(|(_, arg)| arg);
+    // 导入的关联和禁止关系按注册的资源类型操作校验
+    load_operations()?;
+
+    let pg_pool = connect_pool().await?;
+    let pg_connect = pg_pool
+        .get()
+        .await
+        .map_err(|_| Status::aborted("pg connection not found"))?;
+    let age_client = Client::connect_age_extend(pg_connect.client())
+        .await
+        .map_err(|_| Status::aborted("pg connection not found"))?;
+
+    match args.first().map(String::as_str) {
+        Some("export") => {
+            let format = match file {
+                Some(path) => PolicyFormat::from_path(path),
+                None if flag("--json") => PolicyFormat::Json,
+                None => PolicyFormat::Yaml,
+            };
+            let document = export_policy(age_client).await?.render(format)?;
+            match file {
+                Some(path) => std::fs::write(path, document)
+                    .map_err(|e| Status::aborted(format!("write {} failed: {}", path, e)))?,
+                None => print!("{}", document),
+            }
+        }
+        Some("import") => {
+            let path = file.ok_or_else(|| Status::invalid_argument(USAGE))?;
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| Status::aborted(format!("read {} failed: {}", path, e)))?;
+            let document = PolicyDocument::parse(&text, PolicyFormat::from_path(path))?;
+            let dry_run = flag("--dry-run");
+
+            ensure_graph(age_client).await?;
+            let changes = import_policy(age_client, &document, flag("--prune"), dry_run).await?;
+            if !dry_run {
+                ensure_policy_class(age_client, &default_policy_class()).await?;
+            }
+            for change in changes.iter() {
+                println!("{}", change);
+            }
+            eprintln!(
+                "{} change(s){}",
+                changes.len(),
+                if dry_run { ", dry run" } else { "" }
+            );
+        }
+        Some("render") => {
+            let view = match from {
+                Some(id) => GraphView::reachable(age_client, id).await?,
+                None => GraphView::full(age_client).await?,
+            };
+            let graphml = flag("--graphml") || file.is_some_and(|path| path.ends_with(".graphml"));
+            let output = if graphml {
+                view.to_graphml()
+            } else {
+                view.to_dot()
+            };
+            match file {
+                Some(path) => std::fs::write(path, output)
+                    .map_err(|e| Status::aborted(format!("write {} failed: {}", path, e)))?,
+                None => print!("{}", output),
+            }
+        }
+        _ => return Err(Status::invalid_argument(USAGE)),
+    }
+    Ok(())
+}
+
+#[volo::main]
+async fn main() {
+    let project_dir = std::env::current_dir().unwrap();
+    // 与 server 共用 entity/.env，也可直接通过环境变量传入 DATABASE_URL
+    let _ = dotenv::from_path(project_dir.parent().unwrap().join("entity").join(".env"));
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&args).await {
+        eprintln!("{}", e.message());
+        std::process::exit(1);
+    }
+}
@@ -1,22 +1,45 @@
 use volo_grpc::server::{Server, ServiceBuilder};
 use std::net::SocketAddr;
 
-use volo_gen::person_center::{UserServer, UserAttributeServer};
+use volo_gen::person_center::{
+    AccessReviewServer, ObjectAttributeServer, ObjectServer, PolicyAdminServer, PolicyClassServer,
+    ProhibitionServer, RelationshipServiceServer, UserAttributeServer, UserServer,
+};
 use layer::postgres::PostgresqlLayer;
-use person_center::controller::{user::UserService, user_attribute::UserAttributeService};
+use person_center::bootstrap::{acquire_worker_id, bootstrap_graph, load_obligations, load_operations, spawn_policy_engine};
+use person_center::controller::{
+    access_review::AccessReviewService, object::ObjectService,
+    object_attribute::ObjectAttributeService, policy_admin::PolicyAdminService,
+    policy_class::PolicyClassService, prohibition::ProhibitionService,
+    relationship::RelationshipServiceImpl, user::UserService, user_attribute::UserAttributeService,
+};
 
 #[volo::main]
 async fn main() {
+    tracing_subscriber::fmt::init();
     let addr = "[::]:8080".parse::<SocketAddr>().unwrap();
     let addr = volo::net::Address::from(addr);
     let project_dir = std::env::current_dir().unwrap();
     dotenv::from_path(project_dir.parent().unwrap().join("entity").join(".env")).unwrap();
+    let lease = acquire_worker_id().await.unwrap();
+    bootstrap_graph().await.unwrap();
+    load_operations().unwrap();
+    load_obligations().unwrap();
+    spawn_policy_engine();
 
     Server::new()
         .add_service(ServiceBuilder::new(UserServer::new(UserService)).build())
         .add_service(ServiceBuilder::new(UserAttributeServer::new(UserAttributeService)).build())
+        .add_service(ServiceBuilder::new(ObjectServer::new(ObjectService)).build())
+        .add_service(ServiceBuilder::new(ObjectAttributeServer::new(ObjectAttributeService)).build())
+        .add_service(ServiceBuilder::new(PolicyClassServer::new(PolicyClassService)).build())
+        .add_service(ServiceBuilder::new(ProhibitionServer::new(ProhibitionService)).build())
+        .add_service(ServiceBuilder::new(RelationshipServiceServer::new(RelationshipServiceImpl)).build())
+        .add_service(ServiceBuilder::new(AccessReviewServer::new(AccessReviewService)).build())
+        .add_service(ServiceBuilder::new(PolicyAdminServer::new(PolicyAdminService)).build())
         .layer_front(PostgresqlLayer)
         .run(addr)
         .await
         .unwrap();
+    lease.release().await.unwrap();
 }
@@ -0,0 +1,146 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use bb8::Pool;
+use bb8_postgres::{
+    tokio_postgres::{Config, GenericClient},
+    PostgresConnectionManager,
+};
+use volo_grpc::Status;
+
+use entity::graph::{
+    access_right::OperationRegistry, edge::migrate_assignment_labels, engine::PolicyEngine,
+    ensure_graph, ensure_policy_class, notify::GraphChange, obligation::ObligationEngine,
+};
+use idgen::{lease::WorkerIdLease, IdGeneratorOptions};
+use layer::middleware::{id_generator_lease, redis_connect_extension};
+use pool::age::{AgeClientExtend, Client, NoTls, NotificationListener};
+
+/// 默认策略类名称的环境变量
+pub const DEFAULT_POLICY_CLASS_ENV: &str = "NGAC_DEFAULT_POLICY_CLASS";
+pub const DEFAULT_POLICY_CLASS: &str = "default";
+
+/// 义务定义文件（JSON 数组）路径的环境变量，未设置时不加载义务
+pub const OBLIGATIONS_FILE_ENV: &str = "NGAC_OBLIGATIONS_FILE";
+
+/// 资源类型自定义操作文件（JSON 对象，资源类型 → 操作数组）路径的环境变量，
+/// 未设置时只允许内置操作
+pub const OPERATIONS_FILE_ENV: &str = "NGAC_OPERATIONS_FILE";
+
+/// 图变更通知连接断开后的重连间隔
+const GRAPH_LISTENER_RETRY: Duration = Duration::from_secs(5);
+
+pub fn default_policy_class() -> String {
+    std::env::var(DEFAULT_POLICY_CLASS_ENV).unwrap_or_else(|_| DEFAULT_POLICY_CLASS.to_owned())
+}
+
+fn database_config() -> Result<Config, Status> {
+    let database_url =
+        std::env::var("DATABASE_URL").map_err(|_| Status::aborted("DATABASE_URL not set"))?;
+    Config::from_str(&database_url).map_err(|e| Status::from_error(Box::new(e)))
+}
+
+/// 以 `DATABASE_URL` 建立单连接的连接池，供启动和命令行工具使用
+pub async fn connect_pool() -> Result<Pool<PostgresConnectionManager<NoTls>>, Status> {
+    let config = database_config()?;
+    Pool::builder()
+        .max_size(1)
+        .build(PostgresConnectionManager::new(config, NoTls))
+        .await
+        .map_err(|e| Status::from_error(Box::new(e)))
+}
+
+/// 从 redis 申请机器码租约并配置全局ID生成器，多个副本不会使用相同的机器码
+///
+/// 返回的租约需持有到服务退出后再释放。
+pub async fn acquire_worker_id() -> Result<WorkerIdLease, Status> {
+    let pool = redis_connect_extension().await.0;
+    id_generator_lease(pool, IdGeneratorOptions::new(0))
+        .await
+        .map_err(|e| Status::aborted(e.to_string()))
+}
+
+/// 启动时确保 ngac 图和默认策略类存在，并迁移旧版本的指派边标签
+pub async fn bootstrap_graph() -> Result<(), Status> {
+    let pg_pool = connect_pool().await?;
+    let pg_connect = pg_pool
+        .get()
+        .await
+        .map_err(|_| Status::aborted("pg connection not found"))?;
+    let age_client = Client::connect_age_extend(pg_connect.client())
+        .await
+        .map_err(|_| Status::aborted("pg connection not found"))?;
+
+    ensure_graph(age_client).await?;
+    migrate_assignment_labels(age_client).await?;
+    ensure_policy_class(age_client, &default_policy_class()).await?;
+    Ok(())
+}
+
+/// 订阅图变更通知后将整个策略图加载到内存，之后按通知同步其他副本写入的变更
+///
+/// 先 LISTEN 再加载，加载期间的变更不会遗漏；通知连接断开时返回，由调用方重连。
+async fn follow_graph_changes() -> Result<(), Status> {
+    let mut listener = NotificationListener::graph_changes(&database_config()?)
+        .await
+        .map_err(|e| Status::from_error(Box::new(e)))?;
+    let pg_pool = connect_pool().await?;
+    let pg_connect = pg_pool
+        .get()
+        .await
+        .map_err(|_| Status::aborted("pg connection not found"))?;
+    let age_client = Client::connect_age_extend(pg_connect.client())
+        .await
+        .map_err(|_| Status::aborted("pg connection not found"))?;
+    PolicyEngine::load(age_client).await?;
+
+    while let Some(notification) = listener.recv().await {
+        let notification = notification.map_err(|e| Status::from_error(Box::new(e)))?;
+        match GraphChange::parse(notification.payload()) {
+            Ok(change) => PolicyEngine::apply(age_client, &change).await,
+            Err(s) => tracing::warn!(
+                payload = notification.payload(),
+                error = s.message(),
+                "ignore graph change"
+            ),
+        }
+    }
+    Err(Status::unavailable("graph change listener closed"))
+}
+
+/// 在后台维护内存策略图；通知中断期间作废内存图，判定回退到数据库，之后重连并重新加载
+pub fn spawn_policy_engine() {
+    tokio::spawn(async {
+        loop {
+            if let Err(s) = follow_graph_changes().await {
+                tracing::warn!(
+                    error = s.message(),
+                    retry_in = ?GRAPH_LISTENER_RETRY,
+                    "policy engine stopped following graph changes"
+                );
+            }
+            PolicyEngine::invalidate();
+            tokio::time::sleep(GRAPH_LISTENER_RETRY).await;
+        }
+    });
+}
+
+/// 从 `NGAC_OBLIGATIONS_FILE` 加载义务，返回加载的数量
+pub fn load_obligations() -> Result<usize, Status> {
+    let Ok(path) = std::env::var(OBLIGATIONS_FILE_ENV) else {
+        return Ok(0);
+    };
+    let json = std::fs::read_to_string(&path)
+        .map_err(|e| Status::aborted(format!("read {} failed: {}", path, e)))?;
+    ObligationEngine::load_json(&json)
+}
+
+/// 从 `NGAC_OPERATIONS_FILE` 注册各资源类型的自定义操作，返回资源类型的数量
+pub fn load_operations() -> Result<usize, Status> {
+    let Ok(path) = std::env::var(OPERATIONS_FILE_ENV) else {
+        return Ok(0);
+    };
+    let json = std::fs::read_to_string(&path)
+        .map_err(|e| Status::aborted(format!("read {} failed: {}", path, e)))?;
+    OperationRegistry::load_json(&json)
+}
@@ -0,0 +1,39 @@
+use volo_grpc::{Status, Request, Response};
+use bb8::Pool;
+use bb8_postgres::{tokio_postgres::GenericClient, PostgresConnectionManager};
+
+use volo_gen::person_center::{
+    AccessReview,
+    WhoCanAccessRequest,
+    WhatCanAccessRequest,
+    AccessEntriesResponse,
+};
+use pool::age::{AgeClientExtend, Client, NoTls};
+
+use crate::service::access_review::{
+    handler_who_can_access,
+    handler_what_can_access,
+};
+
+#[derive(Debug, Default)]
+pub struct AccessReviewService;
+
+impl AccessReview for AccessReviewService {
+    async fn who_can_access(&self, req: Request<WhoCanAccessRequest>) -> Result<Response<AccessEntriesResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_who_can_access(data, age_client).await
+    }
+
+    async fn what_can_access(&self, req: Request<WhatCanAccessRequest>) -> Result<Response<AccessEntriesResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_what_can_access(data, age_client).await
+    }
+}
@@ -0,0 +1,8 @@
+use volo_grpc::metadata::MetadataMap;
+
+/// 网关在 metadata 中传入的发起请求的用户 id
+pub const ACTOR_ID_KEY: &str = "x-user-id";
+
+pub fn actor_id(metadata: &MetadataMap) -> Option<i64> {
+    metadata.get(ACTOR_ID_KEY)?.to_str().ok()?.parse().ok()
+}
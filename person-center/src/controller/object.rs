@@ -0,0 +1,64 @@
+use volo_grpc::{Status, Request, Response};
+use bb8::Pool;
+use bb8_postgres::{tokio_postgres::GenericClient, PostgresConnectionManager};
+
+use volo_gen::person_center::{
+    Object,
+    AddObjectRequest,
+    ObjectResponse,
+    EditObjectRequest,
+    FilterObjectRequest,
+    ObjectsResponse,
+    PreciseObjectRequest,
+    Accessable,
+};
+use pool::age::{AgeClientExtend, Client, NoTls};
+
+use crate::controller::metadata::actor_id;
+use crate::service::object::{
+    handler_add_object,
+    handler_edit_object,
+    handler_search_object,
+    handler_remove_object,
+};
+
+#[derive(Debug, Default)]
+pub struct ObjectService;
+
+impl Object for ObjectService {
+    async fn add_object(&self, req: Request<AddObjectRequest>) -> Result<Response<ObjectResponse>, Status> {
+        let (metadata, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_add_object(data, age_client, actor_id(&metadata)).await
+    }
+
+    async fn edit_object(&self, req: Request<EditObjectRequest>) -> Result<Response<ObjectResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_edit_object(data, age_client).await
+    }
+
+    async fn filter_object(&self, req: Request<FilterObjectRequest>) -> Result<Response<ObjectsResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_search_object(data, age_client).await
+    }
+
+    async fn remove_object(&self, req: Request<PreciseObjectRequest>) -> Result<Response<Accessable>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_remove_object(data, age_client).await
+    }
+}
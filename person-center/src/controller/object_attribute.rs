@@ -0,0 +1,64 @@
+use volo_grpc::{Status, Request, Response};
+use bb8::Pool;
+use bb8_postgres::{tokio_postgres::GenericClient, PostgresConnectionManager};
+
+use volo_gen::person_center::{
+    ObjectAttribute,
+    AddObjectAttributeRequest,
+    ObjectAttributeResponse,
+    EditObjectAttributeRequest,
+    FilterObjectAttributeRequest,
+    ObjectAttributesResponse,
+    PreciseObjectAttributeRequest,
+    Accessable,
+};
+use pool::age::{AgeClientExtend, Client, NoTls};
+
+use crate::controller::metadata::actor_id;
+use crate::service::object_attribute::{
+    handler_add_object_attribute,
+    handler_edit_object_attribute,
+    handler_search_object_attribute,
+    handler_remove_object_attribute,
+};
+
+#[derive(Debug, Default)]
+pub struct ObjectAttributeService;
+
+impl ObjectAttribute for ObjectAttributeService {
+    async fn add_object_attribute(&self, req: Request<AddObjectAttributeRequest>) -> Result<Response<ObjectAttributeResponse>, Status> {
+        let (metadata, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_add_object_attribute(data, age_client, actor_id(&metadata)).await
+    }
+
+    async fn edit_object_attribute(&self, req: Request<EditObjectAttributeRequest>) -> Result<Response<ObjectAttributeResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_edit_object_attribute(data, age_client).await
+    }
+
+    async fn filter_object_attribute(&self, req: Request<FilterObjectAttributeRequest>) -> Result<Response<ObjectAttributesResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_search_object_attribute(data, age_client).await
+    }
+
+    async fn remove_object_attribute(&self, req: Request<PreciseObjectAttributeRequest>) -> Result<Response<Accessable>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_remove_object_attribute(data, age_client).await
+    }
+}
@@ -0,0 +1,52 @@
+use volo_grpc::{Status, Request, Response};
+use bb8::Pool;
+use bb8_postgres::{tokio_postgres::GenericClient, PostgresConnectionManager};
+
+use volo_gen::person_center::{
+    PolicyAdmin,
+    ExportPolicyRequest,
+    PolicyDocumentResponse,
+    ImportPolicyRequest,
+    ImportPolicyResponse,
+    CheckConsistencyRequest,
+    ConsistencyResponse,
+};
+use pool::age::{AgeClientExtend, Client, NoTls};
+
+use crate::service::policy_admin::{
+    handler_export_policy,
+    handler_import_policy,
+    handler_check_consistency,
+};
+
+#[derive(Debug, Default)]
+pub struct PolicyAdminService;
+
+impl PolicyAdmin for PolicyAdminService {
+    async fn export_policy(&self, req: Request<ExportPolicyRequest>) -> Result<Response<PolicyDocumentResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_export_policy(data, age_client).await
+    }
+
+    async fn import_policy(&self, req: Request<ImportPolicyRequest>) -> Result<Response<ImportPolicyResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_import_policy(data, age_client).await
+    }
+
+    async fn check_consistency(&self, req: Request<CheckConsistencyRequest>) -> Result<Response<ConsistencyResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_check_consistency(data, age_client).await
+    }
+}
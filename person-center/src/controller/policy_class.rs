@@ -0,0 +1,52 @@
+use volo_grpc::{Status, Request, Response};
+use bb8::Pool;
+use bb8_postgres::{tokio_postgres::GenericClient, PostgresConnectionManager};
+
+use volo_gen::person_center::{
+    PolicyClass,
+    AddPolicyClassRequest,
+    PolicyClassResponse,
+    FilterPolicyClassRequest,
+    PolicyClassesResponse,
+    PrecisePolicyClassRequest,
+    Accessable,
+};
+use pool::age::{AgeClientExtend, Client, NoTls};
+
+use crate::service::policy_class::{
+    handler_add_policy_class,
+    handler_search_policy_class,
+    handler_remove_policy_class,
+};
+
+#[derive(Debug, Default)]
+pub struct PolicyClassService;
+
+impl PolicyClass for PolicyClassService {
+    async fn add_policy_class(&self, req: Request<AddPolicyClassRequest>) -> Result<Response<PolicyClassResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_add_policy_class(data, age_client).await
+    }
+
+    async fn filter_policy_class(&self, req: Request<FilterPolicyClassRequest>) -> Result<Response<PolicyClassesResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_search_policy_class(data, age_client).await
+    }
+
+    async fn remove_policy_class(&self, req: Request<PrecisePolicyClassRequest>) -> Result<Response<Accessable>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_remove_policy_class(data, age_client).await
+    }
+}
@@ -0,0 +1,63 @@
+use volo_grpc::{Status, Request, Response};
+use bb8::Pool;
+use bb8_postgres::{tokio_postgres::GenericClient, PostgresConnectionManager};
+
+use volo_gen::person_center::{
+    Prohibition,
+    AddProhibitionRequest,
+    EditProhibitionRequest,
+    FilterProhibitionRequest,
+    PreciseProhibitionRequest,
+    ProhibitionResponse,
+    ProhibitionsResponse,
+    Accessable,
+};
+use pool::age::{AgeClientExtend, Client, NoTls};
+
+use crate::service::prohibition::{
+    handler_add_prohibition,
+    handler_edit_prohibition,
+    handler_search_prohibition,
+    handler_remove_prohibition,
+};
+
+#[derive(Debug, Default)]
+pub struct ProhibitionService;
+
+impl Prohibition for ProhibitionService {
+    async fn add_prohibition(&self, req: Request<AddProhibitionRequest>) -> Result<Response<ProhibitionResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_add_prohibition(data, age_client).await
+    }
+
+    async fn edit_prohibition(&self, req: Request<EditProhibitionRequest>) -> Result<Response<ProhibitionResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_edit_prohibition(data, age_client).await
+    }
+
+    async fn filter_prohibition(&self, req: Request<FilterProhibitionRequest>) -> Result<Response<ProhibitionsResponse>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_search_prohibition(data, age_client).await
+    }
+
+    async fn remove_prohibition(&self, req: Request<PreciseProhibitionRequest>) -> Result<Response<Accessable>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_remove_prohibition(data, age_client).await
+    }
+}
@@ -0,0 +1,50 @@
+use volo_grpc::{Status, Request, Response};
+use bb8::Pool;
+use bb8_postgres::{tokio_postgres::GenericClient, PostgresConnectionManager};
+
+use volo_gen::person_center::{
+    RelationshipService,
+    Association,
+    ResetAssociationRequest,
+    Accessable,
+};
+use pool::age::{AgeClientExtend, Client, NoTls};
+
+use crate::controller::metadata::actor_id;
+use crate::service::relationship::{
+    handler_add_association,
+    handler_remove_association,
+    handler_update_association,
+};
+
+#[derive(Debug, Default)]
+pub struct RelationshipServiceImpl;
+
+impl RelationshipService for RelationshipServiceImpl {
+    async fn add_association(&self, req: Request<Association>) -> Result<Response<Accessable>, Status> {
+        let (metadata, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_add_association(data, age_client, actor_id(&metadata)).await
+    }
+
+    async fn remove_association(&self, req: Request<Association>) -> Result<Response<Accessable>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_remove_association(data, age_client).await
+    }
+
+    async fn update_association(&self, req: Request<ResetAssociationRequest>) -> Result<Response<Accessable>, Status> {
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_update_association(data, age_client).await
+    }
+}
@@ -18,8 +18,9 @@ use volo_gen::person_center::{
 };
 use pool::age::{AgeClientExtend, Client, NoTls};
 
+use crate::controller::metadata::actor_id;
 use crate::service::user::{
-    handler_add_user, handler_login, handler_search_user
+    handler_add_user, handler_check_permission, handler_delete_user, handler_login, handler_search_user
 };
 
 #[derive(Debug, Default)]
@@ -45,17 +46,22 @@ impl User for UserService {
     }
 
 	async fn insert_user(&self, req: Request<PrivateUserInfo>) -> Result<Response<UserResponse>, Status> {
-        let (_, extensions, data) = req.into_parts();
+        let (metadata, extensions, data) = req.into_parts();
         let db = extensions.get::<DatabaseConnection>().ok_or_else(|| Status::aborted("pg orm connection not found"))?;
         let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
         let pg_connect = pg_pool.get().await.map_err(|e| Status::aborted("pg connection not found"))?;
         let pg_client = pg_connect.client();
         let age_client = Client::connect_age_extend(pg_client).await.map_err(|e| Status::aborted("pg connection not found"))?;
-        handler_add_user(data, db, age_client).await
+        handler_add_user(data, db, age_client, actor_id(&metadata)).await
     }
 
 	async fn delete_user(&self, req: Request<UserDetailRequest>) -> Result<Response<Accessable>, Status> {
-        todo!()
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_delete_user(data, age_client).await
     }
 
 	async fn login(&self, req: Request<LoginForm>) -> Result<Response<Logged>, Status> {
@@ -69,6 +75,11 @@ impl User for UserService {
     }
 
 	async fn check_permission(&self, req: Request<CheckPermissionRequest>) -> Result<Response<Accessable>, Status> {
-        todo!()
+        let (_, extensions, data) = req.into_parts();
+        let pg_pool = extensions.get::<Pool<PostgresConnectionManager<NoTls>>>().ok_or_else(|| Status::aborted("pg connection not found"))?;
+        let pg_connect = pg_pool.get().await.map_err(|_| Status::aborted("pg connection not found"))?;
+        let pg_client = pg_connect.client();
+        let age_client = Client::connect_age_extend(pg_client).await.map_err(|_| Status::aborted("pg connection not found"))?;
+        handler_check_permission(data, age_client).await
     }
 }
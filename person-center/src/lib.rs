@@ -1,2 +1,3 @@
 pub mod service;
-pub mod controller;
\ No newline at end of file
+pub mod controller;
+pub mod bootstrap;
\ No newline at end of file
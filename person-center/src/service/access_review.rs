@@ -0,0 +1,56 @@
+use volo::FastStr;
+use volo_grpc::{Response, Status};
+
+use entity::graph::{
+    access_right::AccessRightSet,
+    review::{self, what_can_access, who_can_access},
+};
+use pool::age::Client;
+use volo_gen::person_center::{
+    AccessEntriesResponse, AccessEntry, DerivationPath, WhatCanAccessRequest, WhoCanAccessRequest,
+};
+
+fn operations_response(operations: &AccessRightSet) -> Vec<FastStr> {
+    operations
+        .iter()
+        .map(|right| right.to_string().into())
+        .collect()
+}
+
+fn access_entries_response(entries: Vec<review::AccessEntry>) -> AccessEntriesResponse {
+    AccessEntriesResponse {
+        entries: entries
+            .iter()
+            .map(|entry| AccessEntry {
+                user_id: entry.user_id,
+                resource_id: entry.resource_id,
+                operations: operations_response(&entry.operations),
+                paths: entry
+                    .paths
+                    .iter()
+                    .map(|path| DerivationPath {
+                        node_ids: path.nodes.clone(),
+                        edge_types: path.edges.iter().map(|edge| edge.label().into()).collect(),
+                        operations: operations_response(&path.operations),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+pub async fn handler_who_can_access(
+    body: WhoCanAccessRequest,
+    age_client: &Client,
+) -> Result<Response<AccessEntriesResponse>, Status> {
+    let entries = who_can_access(age_client, body.resource_id).await?;
+    Ok(Response::new(access_entries_response(entries)))
+}
+
+pub async fn handler_what_can_access(
+    body: WhatCanAccessRequest,
+    age_client: &Client,
+) -> Result<Response<AccessEntriesResponse>, Status> {
+    let entries = what_can_access(age_client, body.user_id).await?;
+    Ok(Response::new(access_entries_response(entries)))
+}
@@ -1,2 +1,10 @@
+pub mod access_review;
+pub mod object;
+pub mod object_attribute;
+pub mod obligation;
+pub mod policy_admin;
+pub mod policy_class;
+pub mod prohibition;
+pub mod relationship;
 pub mod user;
 pub mod user_attribute;
\ No newline at end of file
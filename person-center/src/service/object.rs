@@ -0,0 +1,181 @@
+use apache_age::Vertex;
+use pilota::AHashMap;
+use volo_grpc::{Code, Response, Status};
+
+use entity::graph::{
+    assignment, create_node, delete_node, engine::PolicyEngine, search_node, search_nodes,
+    update_node, Assignment, DeletePolicy, NodeType, NodeTypeObject, Object, VertexTypeObject,
+};
+use pool::age::Client;
+use volo_gen::person_center::{
+    Accessable, AddObjectRequest, EditObjectRequest, FilterObjectRequest, ObjectInfo,
+    ObjectResponse, ObjectsResponse, PreciseObjectRequest,
+};
+
+use crate::service::obligation::{emit_assigned, emit_node_created};
+
+fn object_response(node: &Vertex<Object>) -> ObjectResponse {
+    ObjectResponse {
+        id: node.id() as i64,
+        object: Some(ObjectInfo {
+            name: node.properties().name.clone().into(),
+            extra: node.properties().properties.clone(),
+        }),
+    }
+}
+
+async fn search_object(age_client: &Client, id: i64) -> Result<Vertex<Object>, Status> {
+    match search_node(
+        age_client,
+        NodeType::Object,
+        None,
+        Some(id),
+        AHashMap::new(),
+    )
+    .await?
+    {
+        VertexTypeObject::Object(node) => Ok(node),
+        _ => Err(Status::aborted("node type error!")),
+    }
+}
+
+pub async fn handler_add_object(
+    body: AddObjectRequest,
+    age_client: &Client,
+    actor_id: Option<i64>,
+) -> Result<Response<ObjectResponse>, Status> {
+    // 查询是否已存在
+    match search_node(
+        age_client,
+        NodeType::Object,
+        Some(&body.name),
+        None,
+        AHashMap::new(),
+    )
+    .await
+    {
+        Ok(_) => {
+            return Err(Status::already_exists(format!(
+                "object: {} exists!",
+                body.name
+            )))
+        }
+        Err(e) => {
+            if e.code() != Code::NotFound {
+                return Err(e);
+            }
+        }
+    };
+
+    // 先确认要指派到的对象属性存在，避免留下孤立节点
+    if let Some(object_attribute_id) = body.object_attribute_id {
+        search_node(
+            age_client,
+            NodeType::ObjectAttribute,
+            None,
+            Some(object_attribute_id),
+            AHashMap::new(),
+        )
+        .await?;
+    }
+
+    let object = Object {
+        name: body.name.to_string(),
+        properties: body.properties.clone(),
+    };
+    if let Some(s) = create_node(age_client, NodeTypeObject::Object(object)).await {
+        return Err(s);
+    }
+
+    // 查询插入结果
+    let node = match search_node(
+        age_client,
+        NodeType::Object,
+        Some(&body.name),
+        None,
+        AHashMap::new(),
+    )
+    .await?
+    {
+        VertexTypeObject::Object(node) => node,
+        _ => return Err(Status::aborted("node type error!")),
+    };
+    PolicyEngine::sync(age_client, &[node.id() as i64]).await;
+
+    if let Some(object_attribute_id) = body.object_attribute_id {
+        let assignment_combination = Assignment::O2OA((node.id() as i64, object_attribute_id));
+        if let Some(e) = assignment(age_client, assignment_combination).await {
+            return Err(e);
+        }
+        PolicyEngine::sync(age_client, &[node.id() as i64]).await;
+        emit_assigned(
+            age_client,
+            actor_id,
+            (node.id() as i64, NodeType::Object),
+            (object_attribute_id, NodeType::ObjectAttribute),
+        )
+        .await;
+    }
+    emit_node_created(
+        age_client,
+        actor_id,
+        node.id() as i64,
+        NodeType::Object,
+        &node.properties().name,
+    )
+    .await;
+    Ok(Response::new(object_response(&node)))
+}
+
+pub async fn handler_edit_object(
+    body: EditObjectRequest,
+    age_client: &Client,
+) -> Result<Response<ObjectResponse>, Status> {
+    update_node(
+        age_client,
+        NodeType::Object,
+        body.object_id,
+        body.name.as_deref(),
+        body.properties,
+    )
+    .await?;
+    let node = search_object(age_client, body.object_id).await?;
+    Ok(Response::new(object_response(&node)))
+}
+
+pub async fn handler_search_object(
+    body: FilterObjectRequest,
+    age_client: &Client,
+) -> Result<Response<ObjectsResponse>, Status> {
+    let rows = search_nodes(
+        age_client,
+        NodeType::Object,
+        body.target_id,
+        body.name.as_deref(),
+        body.properties,
+    )
+    .await?;
+    let objects = rows
+        .iter()
+        .map(|row| {
+            let node: Vertex<Object> = row.get(0);
+            object_response(&node)
+        })
+        .collect();
+    Ok(Response::new(ObjectsResponse { objects }))
+}
+
+pub async fn handler_remove_object(
+    body: PreciseObjectRequest,
+    age_client: &Client,
+) -> Result<Response<Accessable>, Status> {
+    delete_node(
+        age_client,
+        NodeType::Object,
+        body.target_id,
+        DeletePolicy::Cascade,
+    )
+    .await?;
+    PolicyEngine::sync(age_client, &[body.target_id]).await;
+    Ok(Response::new(Accessable { accessable: true }))
+}
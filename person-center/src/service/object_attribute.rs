@@ -0,0 +1,180 @@
+use apache_age::Vertex;
+use pilota::AHashMap;
+use volo_grpc::{Code, Response, Status};
+
+use entity::graph::{
+    assignment, create_node, delete_node, engine::PolicyEngine, search_node, search_nodes,
+    update_node, Assignment, DeletePolicy, NodeType, NodeTypeObject, ObjectAttribute,
+    VertexTypeObject,
+};
+use pool::age::Client;
+use volo_gen::person_center::{
+    Accessable, AddObjectAttributeRequest, EditObjectAttributeRequest,
+    FilterObjectAttributeRequest, ObjectAttributeInfo, ObjectAttributeOriginNodeType,
+    ObjectAttributeResponse, ObjectAttributesResponse, PreciseObjectAttributeRequest,
+};
+
+use crate::service::obligation::{emit_assigned, emit_node_created};
+
+fn object_attribute_response(node: &Vertex<ObjectAttribute>) -> ObjectAttributeResponse {
+    ObjectAttributeResponse {
+        id: node.id() as i64,
+        object_attribute: Some(ObjectAttributeInfo {
+            name: node.properties().name.clone().into(),
+            extra: node.properties().properties.clone(),
+        }),
+    }
+}
+
+async fn search_object_attribute(
+    age_client: &Client,
+    name: Option<&str>,
+    id: Option<i64>,
+) -> Result<Vertex<ObjectAttribute>, Status> {
+    match search_node(
+        age_client,
+        NodeType::ObjectAttribute,
+        name,
+        id,
+        AHashMap::new(),
+    )
+    .await?
+    {
+        VertexTypeObject::ObjectAttribute(node) => Ok(node),
+        _ => Err(Status::aborted("node type error!")),
+    }
+}
+
+pub async fn handler_add_object_attribute(
+    body: AddObjectAttributeRequest,
+    age_client: &Client,
+    actor_id: Option<i64>,
+) -> Result<Response<ObjectAttributeResponse>, Status> {
+    // 查询是否已存在
+    match search_object_attribute(age_client, Some(&body.name), None).await {
+        Ok(oa) => {
+            return Err(Status::already_exists(format!(
+                "object attribute: {} exists!",
+                oa.properties().name,
+            )))
+        }
+        Err(e) => {
+            if e.code() != Code::NotFound {
+                return Err(e);
+            }
+        }
+    };
+
+    // 查找原始origin节点
+    let origin_node_type = if body.origin_node_type == ObjectAttributeOriginNodeType::OBJECT {
+        NodeType::Object
+    } else {
+        NodeType::ObjectAttribute
+    };
+    search_node(
+        age_client,
+        origin_node_type,
+        None,
+        Some(body.origin_id),
+        AHashMap::new(),
+    )
+    .await?;
+
+    let object_attribute = ObjectAttribute {
+        name: body.name.to_string(),
+        properties: body.properties.clone(),
+    };
+    if let Some(s) = create_node(
+        age_client,
+        NodeTypeObject::ObjectAttribute(object_attribute),
+    )
+    .await
+    {
+        return Err(s);
+    }
+
+    // 查询插入结果
+    let node = search_object_attribute(age_client, Some(&body.name), None).await?;
+    PolicyEngine::sync(age_client, &[node.id() as i64]).await;
+
+    // 添加指派关系
+    let assignment_combination = match origin_node_type {
+        NodeType::Object => Assignment::O2OA((body.origin_id, node.id() as i64)),
+        _ => Assignment::OA2OA((body.origin_id, node.id() as i64)),
+    };
+    if let Some(e) = assignment(age_client, assignment_combination).await {
+        return Err(e);
+    }
+    PolicyEngine::sync(age_client, &[node.id() as i64]).await;
+    emit_assigned(
+        age_client,
+        actor_id,
+        (body.origin_id, origin_node_type),
+        (node.id() as i64, NodeType::ObjectAttribute),
+    )
+    .await;
+    emit_node_created(
+        age_client,
+        actor_id,
+        node.id() as i64,
+        NodeType::ObjectAttribute,
+        &node.properties().name,
+    )
+    .await;
+    Ok(Response::new(object_attribute_response(&node)))
+}
+
+pub async fn handler_edit_object_attribute(
+    body: EditObjectAttributeRequest,
+    age_client: &Client,
+) -> Result<Response<ObjectAttributeResponse>, Status> {
+    update_node(
+        age_client,
+        NodeType::ObjectAttribute,
+        body.object_attribute_id,
+        body.name.as_deref(),
+        body.properties,
+    )
+    .await?;
+    let node = search_object_attribute(age_client, None, Some(body.object_attribute_id)).await?;
+    Ok(Response::new(object_attribute_response(&node)))
+}
+
+pub async fn handler_search_object_attribute(
+    body: FilterObjectAttributeRequest,
+    age_client: &Client,
+) -> Result<Response<ObjectAttributesResponse>, Status> {
+    let rows = search_nodes(
+        age_client,
+        NodeType::ObjectAttribute,
+        body.target_id,
+        body.name.as_deref(),
+        body.properties,
+    )
+    .await?;
+    let object_attributes = rows
+        .iter()
+        .map(|row| {
+            let node: Vertex<ObjectAttribute> = row.get(0);
+            object_attribute_response(&node)
+        })
+        .collect();
+    Ok(Response::new(ObjectAttributesResponse {
+        object_attributes,
+    }))
+}
+
+pub async fn handler_remove_object_attribute(
+    body: PreciseObjectAttributeRequest,
+    age_client: &Client,
+) -> Result<Response<Accessable>, Status> {
+    delete_node(
+        age_client,
+        NodeType::ObjectAttribute,
+        body.target_id,
+        DeletePolicy::Cascade,
+    )
+    .await?;
+    PolicyEngine::sync(age_client, &[body.target_id]).await;
+    Ok(Response::new(Accessable { accessable: true }))
+}
@@ -0,0 +1,61 @@
+use entity::graph::engine::PolicyEngine;
+use entity::graph::obligation::{GraphEvent, ObligationEngine};
+use entity::graph::NodeType;
+use pool::age::Client;
+
+/// 义务的响应可能修改任意节点，执行了义务时重新加载内存策略图
+///
+/// 触发义务的变更已经提交，义务执行失败只记录日志，不让已成功的请求返回错误；
+/// 失败的义务已回滚自己的响应，不会留下部分修改。
+async fn evaluate(age_client: &Client, event: &GraphEvent) {
+    let evaluation = match ObligationEngine::evaluate(age_client, event).await {
+        Ok(evaluation) => evaluation,
+        Err(s) => {
+            tracing::error!(?event, error = s.message(), "evaluate obligations failed");
+            return;
+        }
+    };
+    for (name, s) in evaluation.failed.iter() {
+        tracing::warn!(obligation = %name, ?event, error = s.message(), "obligation failed");
+    }
+    if !evaluation.applied.is_empty() && PolicyEngine::is_loaded() {
+        if let Err(s) = PolicyEngine::load(age_client).await {
+            tracing::error!(error = s.message(), "reload policy engine failed");
+            PolicyEngine::invalidate();
+        }
+    }
+}
+
+/// 节点创建成功后触发义务
+pub async fn emit_node_created(
+    age_client: &Client,
+    actor_id: Option<i64>,
+    node_id: i64,
+    node_type: NodeType,
+    name: &str,
+) {
+    let event = GraphEvent::NodeCreated {
+        actor_id,
+        node_id,
+        node_type,
+        name: name.to_owned(),
+    };
+    evaluate(age_client, &event).await
+}
+
+/// 指派成功后触发义务
+pub async fn emit_assigned(
+    age_client: &Client,
+    actor_id: Option<i64>,
+    origin: (i64, NodeType),
+    target: (i64, NodeType),
+) {
+    let event = GraphEvent::Assigned {
+        actor_id,
+        origin_id: origin.0,
+        origin_type: origin.1,
+        target_id: target.0,
+        target_type: target.1,
+    };
+    evaluate(age_client, &event).await
+}
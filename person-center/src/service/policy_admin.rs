@@ -0,0 +1,91 @@
+use volo_grpc::{Response, Status};
+
+use entity::graph::{
+    decision::EdgeRecord,
+    engine::PolicyEngine,
+    ensure_policy_class,
+    policy::{self, export_policy, import_policy, PolicyDocument},
+    NodeType,
+};
+use pool::age::Client;
+use volo_gen::person_center::{
+    CheckConsistencyRequest, ConsistencyResponse, ExportPolicyRequest, ImportPolicyRequest,
+    ImportPolicyResponse, PolicyDocumentResponse, PolicyFormat,
+};
+
+use crate::bootstrap::default_policy_class;
+
+fn describe_node((id, node_type): &(i64, NodeType)) -> String {
+    format!("{}:{}", node_type, id)
+}
+
+fn describe_edge(edge: &EdgeRecord) -> String {
+    format!("{} -[{}]-> {}", edge.start_id, edge.label, edge.end_id)
+}
+
+fn policy_format(format: PolicyFormat) -> policy::PolicyFormat {
+    if format == PolicyFormat::JSON {
+        policy::PolicyFormat::Json
+    } else {
+        policy::PolicyFormat::Yaml
+    }
+}
+
+pub async fn handler_export_policy(
+    body: ExportPolicyRequest,
+    age_client: &Client,
+) -> Result<Response<PolicyDocumentResponse>, Status> {
+    let document = export_policy(age_client)
+        .await?
+        .render(policy_format(body.format))?;
+    Ok(Response::new(PolicyDocumentResponse {
+        document: document.into(),
+    }))
+}
+
+pub async fn handler_import_policy(
+    body: ImportPolicyRequest,
+    age_client: &Client,
+) -> Result<Response<ImportPolicyResponse>, Status> {
+    let document = PolicyDocument::parse(&body.document, policy_format(body.format))?;
+    let changes = import_policy(age_client, &document, body.prune, body.dry_run).await?;
+    // prune 可能删掉默认策略类，保证其始终存在
+    if !body.dry_run {
+        ensure_policy_class(age_client, &default_policy_class()).await?;
+        if PolicyEngine::is_loaded() {
+            PolicyEngine::load(age_client).await?;
+        }
+    }
+    Ok(Response::new(ImportPolicyResponse {
+        changes: changes
+            .iter()
+            .map(|change| change.to_string().into())
+            .collect(),
+    }))
+}
+
+pub async fn handler_check_consistency(
+    body: CheckConsistencyRequest,
+    age_client: &Client,
+) -> Result<Response<ConsistencyResponse>, Status> {
+    let report = PolicyEngine::check_consistency(age_client, body.repair).await?;
+    let missing = report
+        .missing_nodes
+        .iter()
+        .map(describe_node)
+        .chain(report.missing_edges.iter().map(describe_edge))
+        .map(Into::into)
+        .collect();
+    let stale = report
+        .stale_nodes
+        .iter()
+        .map(describe_node)
+        .chain(report.stale_edges.iter().map(describe_edge))
+        .map(Into::into)
+        .collect();
+    Ok(Response::new(ConsistencyResponse {
+        consistent: report.is_consistent(),
+        missing,
+        stale,
+    }))
+}
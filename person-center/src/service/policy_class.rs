@@ -0,0 +1,134 @@
+use apache_age::Vertex;
+use pilota::AHashMap;
+use volo_grpc::{Code, Response, Status};
+
+use entity::graph::{
+    create_node, delete_node, engine::PolicyEngine, list_nodes, search_node, search_nodes,
+    DeletePolicy, NodeType, NodeTypeObject, PolicyClass, VertexTypeObject,
+};
+use pool::age::Client;
+use volo_gen::person_center::{
+    Accessable, AddPolicyClassRequest, FilterPolicyClassRequest, PolicyClassInfo,
+    PolicyClassResponse, PolicyClassesResponse, PrecisePolicyClassRequest,
+};
+
+use crate::bootstrap::default_policy_class;
+
+fn policy_class_response(node: &Vertex<PolicyClass>) -> PolicyClassResponse {
+    PolicyClassResponse {
+        id: node.id() as i64,
+        policy_class: Some(PolicyClassInfo {
+            name: node.properties().name.clone().into(),
+            extra: node.properties().properties.clone(),
+        }),
+    }
+}
+
+pub async fn handler_add_policy_class(
+    body: AddPolicyClassRequest,
+    age_client: &Client,
+) -> Result<Response<PolicyClassResponse>, Status> {
+    // 查询是否已存在
+    match search_node(
+        age_client,
+        NodeType::PolicyClass,
+        Some(&body.name),
+        None,
+        AHashMap::new(),
+    )
+    .await
+    {
+        Ok(_) => {
+            return Err(Status::already_exists(format!(
+                "policy class: {} exists!",
+                body.name
+            )))
+        }
+        Err(e) => {
+            if e.code() != Code::NotFound {
+                return Err(e);
+            }
+        }
+    };
+
+    let policy_class = PolicyClass {
+        name: body.name.to_string(),
+        properties: body.properties.clone(),
+    };
+    if let Some(s) = create_node(age_client, NodeTypeObject::PolicyClass(policy_class)).await {
+        return Err(s);
+    }
+
+    // 查询插入结果
+    match search_node(
+        age_client,
+        NodeType::PolicyClass,
+        Some(&body.name),
+        None,
+        AHashMap::new(),
+    )
+    .await?
+    {
+        VertexTypeObject::PolicyClass(node) => {
+            PolicyEngine::sync(age_client, &[node.id() as i64]).await;
+            Ok(Response::new(policy_class_response(&node)))
+        }
+        _ => Err(Status::aborted("node type error!")),
+    }
+}
+
+pub async fn handler_search_policy_class(
+    body: FilterPolicyClassRequest,
+    age_client: &Client,
+) -> Result<Response<PolicyClassesResponse>, Status> {
+    let rows = if body.name.is_none() && body.target_id.is_none() {
+        list_nodes(age_client, NodeType::PolicyClass, body.properties).await?
+    } else {
+        search_nodes(
+            age_client,
+            NodeType::PolicyClass,
+            body.target_id,
+            body.name.as_deref(),
+            body.properties,
+        )
+        .await?
+    };
+    let policy_classes = rows
+        .iter()
+        .map(|row| {
+            let node: Vertex<PolicyClass> = row.get(0);
+            policy_class_response(&node)
+        })
+        .collect();
+    Ok(Response::new(PolicyClassesResponse { policy_classes }))
+}
+
+pub async fn handler_remove_policy_class(
+    body: PrecisePolicyClassRequest,
+    age_client: &Client,
+) -> Result<Response<Accessable>, Status> {
+    if let VertexTypeObject::PolicyClass(node) = search_node(
+        age_client,
+        NodeType::PolicyClass,
+        None,
+        Some(body.target_id),
+        AHashMap::new(),
+    )
+    .await?
+    {
+        if node.properties().name == default_policy_class() {
+            return Err(Status::failed_precondition(
+                "the default policy class cannot be removed!",
+            ));
+        }
+    }
+    delete_node(
+        age_client,
+        NodeType::PolicyClass,
+        body.target_id,
+        DeletePolicy::Cascade,
+    )
+    .await?;
+    PolicyEngine::sync(age_client, &[body.target_id]).await;
+    Ok(Response::new(Accessable { accessable: true }))
+}
@@ -0,0 +1,107 @@
+use volo_grpc::{Response, Status};
+
+use entity::graph::{
+    access_right::AccessRightSet,
+    engine::PolicyEngine,
+    prohibition::{
+        create_prohibition, delete_prohibition, search_prohibitions, update_prohibition,
+        Prohibition, ProhibitionContainer as Container,
+    },
+};
+use pool::age::Client;
+use volo_gen::person_center::{
+    Accessable, AddProhibitionRequest, EditProhibitionRequest, FilterProhibitionRequest,
+    PreciseProhibitionRequest, ProhibitionContainer, ProhibitionResponse, ProhibitionsResponse,
+};
+
+fn to_containers(containers: &[ProhibitionContainer]) -> Vec<Container> {
+    containers
+        .iter()
+        .map(|c| Container {
+            id: c.container_id,
+            complement: c.complement,
+        })
+        .collect()
+}
+
+fn prohibition_response(prohibition: &Prohibition) -> ProhibitionResponse {
+    ProhibitionResponse {
+        name: prohibition.name.clone().into(),
+        subject_id: prohibition.subject_id,
+        operations: prohibition
+            .operations
+            .iter()
+            .map(|right| right.to_string().into())
+            .collect(),
+        intersection: prohibition.intersection,
+        containers: prohibition
+            .containers
+            .iter()
+            .map(|c| ProhibitionContainer {
+                container_id: c.id,
+                complement: c.complement,
+            })
+            .collect(),
+    }
+}
+
+pub async fn handler_add_prohibition(
+    body: AddProhibitionRequest,
+    age_client: &Client,
+) -> Result<Response<ProhibitionResponse>, Status> {
+    let prohibition = Prohibition {
+        name: body.name.to_string(),
+        subject_id: body.subject_id,
+        operations: AccessRightSet::from(&body.operations[..]),
+        intersection: body.intersection,
+        containers: to_containers(&body.containers),
+    };
+    create_prohibition(age_client, &prohibition).await?;
+    PolicyEngine::sync(age_client, &[prohibition.subject_id]).await;
+    Ok(Response::new(prohibition_response(&prohibition)))
+}
+
+pub async fn handler_edit_prohibition(
+    body: EditProhibitionRequest,
+    age_client: &Client,
+) -> Result<Response<ProhibitionResponse>, Status> {
+    let operations =
+        (!body.operations.is_empty()).then(|| AccessRightSet::from(&body.operations[..]));
+    let containers = (!body.containers.is_empty()).then(|| to_containers(&body.containers));
+    let prohibition = update_prohibition(
+        age_client,
+        &body.name,
+        operations,
+        body.intersection,
+        containers,
+    )
+    .await?;
+    PolicyEngine::sync(age_client, &[prohibition.subject_id]).await;
+    Ok(Response::new(prohibition_response(&prohibition)))
+}
+
+pub async fn handler_search_prohibition(
+    body: FilterProhibitionRequest,
+    age_client: &Client,
+) -> Result<Response<ProhibitionsResponse>, Status> {
+    let prohibitions = search_prohibitions(age_client, body.subject_id, body.name.as_deref())
+        .await?
+        .iter()
+        .map(prohibition_response)
+        .collect();
+    Ok(Response::new(ProhibitionsResponse { prohibitions }))
+}
+
+pub async fn handler_remove_prohibition(
+    body: PreciseProhibitionRequest,
+    age_client: &Client,
+) -> Result<Response<Accessable>, Status> {
+    let subject_ids: Vec<i64> = search_prohibitions(age_client, None, Some(&body.name))
+        .await?
+        .iter()
+        .map(|prohibition| prohibition.subject_id)
+        .collect();
+    delete_prohibition(age_client, &body.name).await?;
+    PolicyEngine::sync(age_client, &subject_ids).await;
+    Ok(Response::new(Accessable { accessable: true }))
+}
@@ -0,0 +1,90 @@
+use volo_grpc::{Response, Status};
+
+use entity::graph::{
+    access_right::AccessRightSet, assignment, delete_edge, engine::PolicyEngine, retarget_edge,
+    search_node_types, Assignment,
+};
+use pool::age::Client;
+use volo_gen::person_center::{Accessable, Association, ResetAssociationRequest};
+
+use crate::service::obligation::emit_assigned;
+
+/// child 指派（或关联）到 parent，边的方向为 child→parent
+pub async fn handler_add_association(
+    body: Association,
+    age_client: &Client,
+    actor_id: Option<i64>,
+) -> Result<Response<Accessable>, Status> {
+    let node_types = search_node_types(age_client, &[body.child_id, body.parent_id]).await?;
+    let (Some(child_type), Some(parent_type)) = (
+        node_types.get(&body.child_id),
+        node_types.get(&body.parent_id),
+    ) else {
+        return Err(Status::not_found("node not found!"));
+    };
+
+    let operations = AccessRightSet::from(&body.operations[..]);
+    if child_type.can_associate_with(parent_type) {
+        if operations.is_empty() {
+            return Err(Status::invalid_argument(
+                "UA -> OA associations require operations!",
+            ));
+        }
+    } else if !operations.is_empty() {
+        return Err(Status::invalid_argument(
+            "operations only apply to UA -> OA associations!",
+        ));
+    }
+    let assignment_combination = Assignment::from_node_types(
+        *child_type,
+        body.child_id,
+        *parent_type,
+        body.parent_id,
+        operations,
+    )
+    .ok_or_else(|| {
+        Status::failed_precondition(format!(
+            "illegal NGAC relation: {:?} -> {:?}",
+            child_type, parent_type
+        ))
+    })?;
+
+    if let Some(s) = assignment(age_client, assignment_combination).await {
+        return Err(s);
+    }
+    PolicyEngine::sync(age_client, &[body.child_id]).await;
+    if child_type.can_assign_to(parent_type) {
+        emit_assigned(
+            age_client,
+            actor_id,
+            (body.child_id, *child_type),
+            (body.parent_id, *parent_type),
+        )
+        .await;
+    }
+    Ok(Response::new(Accessable { accessable: true }))
+}
+
+pub async fn handler_remove_association(
+    body: Association,
+    age_client: &Client,
+) -> Result<Response<Accessable>, Status> {
+    delete_edge(age_client, body.child_id, body.parent_id).await?;
+    PolicyEngine::sync(age_client, &[body.child_id]).await;
+    Ok(Response::new(Accessable { accessable: true }))
+}
+
+pub async fn handler_update_association(
+    body: ResetAssociationRequest,
+    age_client: &Client,
+) -> Result<Response<Accessable>, Status> {
+    retarget_edge(
+        age_client,
+        body.origin_id,
+        body.old_target_id,
+        body.new_target_id,
+    )
+    .await?;
+    PolicyEngine::sync(age_client, &[body.origin_id]).await;
+    Ok(Response::new(Accessable { accessable: true }))
+}
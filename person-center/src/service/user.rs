@@ -8,19 +8,26 @@ use serde_json::json;
 use volo_grpc::{Code, Response, Status};
 
 use entity::{
-    graph::{create_node, search_node, NodeType, NodeTypeObject, User, VertexTypeObject},
+    graph::{
+        create_node, decision::check_permission, delete_node_tx, engine::PolicyEngine,
+        in_transaction, search_node, DeletePolicy, NodeType, NodeTypeObject, User, VertexTypeObject,
+    },
     user_property,
 };
 use pool::age::Client;
 use utils::{encryption::encryption, extra_to_outer};
 use volo_gen::person_center::{
-    FilterUserRequest, Logged, LoginForm, PrivateUserInfo, UserInfo, UserResponse, UsersResponse,
+    Accessable, CheckPermissionRequest, FilterUserRequest, Logged, LoginForm, PrivateUserInfo,
+    UserDetailRequest, UserInfo, UserResponse, UsersResponse,
 };
 
+use crate::service::obligation::emit_node_created;
+
 pub async fn handler_add_user(
     body: PrivateUserInfo,
     db: &DatabaseConnection,
     age_client: &Client,
+    actor_id: Option<i64>,
 ) -> Result<Response<UserResponse>, Status> {
     let user_name = body.name;
 
@@ -85,6 +92,7 @@ pub async fn handler_add_user(
         Err(s) => return Err(s),
     };
     if let VertexTypeObject::User(node) = node {
+        PolicyEngine::sync(age_client, &[node.id() as i64]).await;
         let extra = match serde_json::to_string(&body.extra) {
             Ok(extra) => extra,
             Err(e) => return Err(Status::from_error(Box::new(e))),
@@ -104,6 +112,14 @@ pub async fn handler_add_user(
         if let Err(e) = user_property.insert(db).await {
             return Err(Status::from_error(Box::new(e)));
         }
+        emit_node_created(
+            age_client,
+            actor_id,
+            node.id() as i64,
+            NodeType::User,
+            &user_name,
+        )
+        .await;
 
         let user_info = Some(UserInfo {
             name: user_name.clone(),
@@ -182,6 +198,28 @@ pub async fn handler_search_user(
     Ok(Response::new(UsersResponse { users: users_res }))
 }
 
+/// 删除用户：`user_property` 与 ngac 图在同一个库中，记录和图中的顶点在同一事务中删除，
+/// 任一步失败时都不删除；提交后再同步内存策略图
+pub async fn handler_delete_user(
+    body: UserDetailRequest,
+    age_client: &Client,
+) -> Result<Response<Accessable>, Status> {
+    in_transaction(age_client, async |tx| {
+        let deleted = tx
+            .execute("DELETE FROM user_property WHERE id = $1", &[&body.id])
+            .await
+            .map_err(|e| Status::from_error(Box::new(e)))?;
+        if deleted == 0 {
+            return Err(Status::not_found(format!("user: {} not found!", body.id)));
+        }
+        // 用户没有子节点，直接断开所有边
+        delete_node_tx(tx, NodeType::User, body.id, DeletePolicy::Cascade).await
+    })
+    .await?;
+    PolicyEngine::sync(age_client, &[body.id]).await;
+    Ok(Response::new(Accessable { accessable: true }))
+}
+
 pub async fn handler_login(
     body: LoginForm,
     db: &DatabaseConnection,
@@ -227,3 +265,27 @@ pub async fn handler_login(
         Err(e) => Err(e),
     }
 }
+
+pub async fn handler_check_permission(
+    body: CheckPermissionRequest,
+    age_client: &Client,
+) -> Result<Response<Accessable>, Status> {
+    // 用户不存在时返回 NotFound，而不是拒绝访问
+    search_node(
+        age_client,
+        NodeType::User,
+        None,
+        Some(body.user_id),
+        AHashMap::new(),
+    )
+    .await?;
+
+    let accessable = check_permission(
+        age_client,
+        body.user_id,
+        body.resource_id,
+        body.operation.as_deref(),
+    )
+    .await?;
+    Ok(Response::new(Accessable { accessable }))
+}
@@ -5,22 +5,26 @@ use apache_age::Vertex;
 
 use entity::{
     graph::{
-        assignment, create_node, search_node, search_user_attribute_node, Assignment, NodeType,
-        NodeTypeObject, UserAttribute, VertexTypeObject,
+        assignment, create_node, delete_node, engine::PolicyEngine, search_node,
+        search_user_attribute_node, Assignment, DeletePolicy, NodeType, NodeTypeObject,
+        UserAttribute, VertexTypeObject,
     },
     user_property,
 };
 use sea_orm::{DatabaseConnection, EntityTrait};
 use volo_gen::person_center::{
-    AddUserAttributeRequest, FilterAttributeRequest, UserAttributeInfo,
-    UserAttributeOriginNodeType, UserAttributeResponse, UserAttributesResponse,
+    Accessable, AddUserAttributeRequest, FilterAttributeRequest, PreciseAttributeRequest,
+    UserAttributeInfo, UserAttributeOriginNodeType, UserAttributeResponse, UserAttributesResponse,
 };
 use pool::age::Client;
 
+use crate::service::obligation::{emit_assigned, emit_node_created};
+
 pub async fn handler_add_user_attribute(
     body: AddUserAttributeRequest,
     db: &DatabaseConnection,
     age_client: &Client,
+    actor_id: Option<i64>,
 ) -> Result<Response<UserAttributeResponse>, Status> {
     // 查询是否已存在
     match search_node(
@@ -83,6 +87,7 @@ pub async fn handler_add_user_attribute(
                                 u.id() as i64,
                                 body.origin_node_type,
                                 AHashMap::new(),
+                                actor_id,
                             )
                             .await
                         } else {
@@ -113,6 +118,7 @@ pub async fn handler_add_user_attribute(
                         ua.id() as i64,
                         body.origin_node_type,
                         AHashMap::new(),
+                        actor_id,
                     )
                     .await
                 } else {
@@ -124,6 +130,20 @@ pub async fn handler_add_user_attribute(
     }
 }
 
+pub async fn handler_remove_user_attribute(
+    body: PreciseAttributeRequest,
+    age_client: &Client,
+) -> Result<Response<Accessable>, Status> {
+    let policy = if body.cascade {
+        DeletePolicy::Cascade
+    } else {
+        DeletePolicy::Refuse
+    };
+    delete_node(age_client, NodeType::UserAttribute, body.target_id, policy).await?;
+    PolicyEngine::sync(age_client, &[body.target_id]).await;
+    Ok(Response::new(Accessable { accessable: true }))
+}
+
 pub async fn handler_search_user_attribute(
     body: FilterAttributeRequest,
     age_client: &Client,
@@ -202,6 +222,9 @@ async fn insert_user_attribute(
         Ok(node) => node,
         Err(s) => return Err(s),
     };
+    if let VertexTypeObject::UserAttribute(ua) = &node {
+        PolicyEngine::sync(client, &[ua.id() as i64]).await;
+    }
     Ok(node)
 }
 
@@ -211,6 +234,7 @@ async fn handler_assignment_combination(
     origin_id: i64,
     origin_node_type: UserAttributeOriginNodeType,
     properties: AHashMap<FastStr, FastStr>,
+    actor_id: Option<i64>,
 ) -> Result<Response<UserAttributeResponse>, Status> {
     if let VertexTypeObject::UserAttribute(node) = node {
         // 添加指派关系
@@ -221,6 +245,28 @@ async fn handler_assignment_combination(
         if let Some(e) = assignment(client, assignment_combination).await {
             return Err(e);
         };
+        PolicyEngine::sync(client, &[node.id() as i64]).await;
+
+        let origin_type = if origin_node_type == UserAttributeOriginNodeType::USER_ATTRIBUTE {
+            NodeType::UserAttribute
+        } else {
+            NodeType::User
+        };
+        emit_assigned(
+            client,
+            actor_id,
+            (origin_id, origin_type),
+            (node.id() as i64, NodeType::UserAttribute),
+        )
+        .await;
+        emit_node_created(
+            client,
+            actor_id,
+            node.id() as i64,
+            NodeType::UserAttribute,
+            &node.properties().name,
+        )
+        .await;
 
         let user_attribute = Some(UserAttributeInfo {
             name: node.properties().name.clone().into(),
@@ -1,10 +1,21 @@
+use std::{
+    future::poll_fn,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
 use axum::async_trait;
-use tokio_postgres::Error;
-pub use tokio_postgres::{Client, NoTls};
+use futures::Stream;
+use tokio::sync::mpsc;
+use tokio_postgres::{AsyncMessage, Config, Error};
+pub use tokio_postgres::{Client, NoTls, Notification};
 
 const LOAD_AGE: &str = "LOAD 'age'";
 const SET_AGE: &str = "SET search_path = ag_catalog, \"$user\", public";
 
+/// ngac 图中节点和边变更的通知通道
+pub const GRAPH_CHANNEL: &str = "ngac_graph_changes";
+
 #[async_trait]
 pub trait AgeClientExtend {
     async fn connect_age_extend(pool: &Client) -> Result<&Client, Error>;
@@ -22,3 +33,66 @@ impl AgeClientExtend for Client {
         Ok(client)
     }
 }
+
+/// 在当前连接上 `pg_notify`，处于事务中时提交后才送达，回滚则丢弃
+pub async fn notify(client: &Client, channel: &str, payload: &str) -> Result<(), Error> {
+    client
+        .execute("SELECT pg_notify($1, $2)", &[&channel, &payload])
+        .await?;
+    Ok(())
+}
+
+/// 在独立连接上 LISTEN 一个通道，以流的形式逐条产出通知
+///
+/// 连接出错时产出该错误后结束，连接关闭时直接结束；丢弃后连接随之关闭。
+pub struct NotificationListener {
+    _client: Client,
+    receiver: mpsc::UnboundedReceiver<Result<Notification, Error>>,
+}
+
+impl NotificationListener {
+    pub async fn listen(config: &Config, channel: &str) -> Result<Self, Error> {
+        let (client, mut connection) = config.connect(NoTls).await?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        // 通知只能通过驱动连接本身取得，由后台任务转发
+        tokio::spawn(async move {
+            while let Some(message) = poll_fn(|cx| connection.poll_message(cx)).await {
+                let forwarded = match message {
+                    Ok(AsyncMessage::Notification(notification)) => sender.send(Ok(notification)),
+                    Ok(_) => continue,
+                    Err(e) => {
+                        let _ = sender.send(Err(e));
+                        break;
+                    }
+                };
+                if forwarded.is_err() {
+                    break;
+                }
+            }
+        });
+        client
+            .batch_execute(&format!("LISTEN \"{}\"", channel.replace('"', "\"\"")))
+            .await?;
+        Ok(NotificationListener {
+            _client: client,
+            receiver,
+        })
+    }
+
+    /// 订阅 [`GRAPH_CHANNEL`]，payload 为 JSON 格式的图变更
+    pub async fn graph_changes(config: &Config) -> Result<Self, Error> {
+        NotificationListener::listen(config, GRAPH_CHANNEL).await
+    }
+
+    pub async fn recv(&mut self) -> Option<Result<Notification, Error>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for NotificationListener {
+    type Item = Result<Notification, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
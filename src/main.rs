@@ -6,8 +6,8 @@
 //     use leptos_axum::{generate_route_list, LeptosRoutes};
 //     use regex::Regex;
 
-//     use idgen::{IdGeneratorOptions, IdHelper};
-//     use layer::{auth::auth_middleware, middleware::{sea_orm_connect_extension, redis_connect_extension, person_center_grpc_extension}};
+//     use idgen::IdGeneratorOptions;
+//     use layer::{auth::auth_middleware, middleware::{sea_orm_connect_extension, redis_connect_extension, person_center_grpc_extension, id_generator_lease}};
 //     use entity::state::OpenApiState;
 //     use sinapis::app::*;
 
@@ -20,10 +20,12 @@
 //     // Generate the list of routes in your Leptos App
 //     let routes = generate_route_list(App);
 
-//     let mut options = IdGeneratorOptions::new(1);
+//     // 机器码从 redis 租约申请，与 extension 共用同一个连接池
+//     let redis = redis_connect_extension().await;
+//     let mut options = IdGeneratorOptions::new(0);
 //     options.worker_id_bit_length = 6;
 //     options.seq_bit_length = 10;
-//     IdHelper::set_id_generator(options);
+//     let lease = id_generator_lease(redis.0.clone(), options).await.unwrap();
 
 //     let open_api_state = OpenApiState {
 //         openapi: vec![Regex::new(r"^/$").unwrap(), Regex::new(r"^/api/login").unwrap()],
@@ -36,7 +38,7 @@
 //             move || shell(leptos_options.clone())
 //         })
 //         .layer(middleware::from_fn_with_state(open_api_state, auth_middleware))
-//         .layer(redis_connect_extension().await)
+//         .layer(redis)
 //         .layer(sea_orm_connect_extension().await)
 //         .layer(person_center_grpc_extension().await)
 //         .fallback(leptos_axum::file_and_error_handler(shell))
@@ -49,6 +51,7 @@
 //     axum::serve(listener, app.into_make_service())
 //         .await
 //         .unwrap();
+//     lease.release().await.unwrap();
 // }
 
 #[cfg(feature = "ssr")]